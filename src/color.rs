@@ -0,0 +1,397 @@
+//! Colors as they are written in alacritty configs and themes.
+
+use serde::{Deserialize, Serialize};
+
+/// A color, written as `#rrggbb`. `#rgb` and `0xrrggbb` are accepted when parsing.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+/// A color that can also refer to the colors of the cell it is drawn on, used by the cursor,
+/// selection, search and hints colors.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum CellRgb {
+    CellForeground,
+    CellBackground,
+    Rgb(Rgb),
+}
+
+/// Why a color string could not be parsed.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ColorParseError {
+    Empty,
+    MissingPrefix(String),
+    InvalidLength(String),
+    InvalidDigit(String, char),
+}
+
+impl std::fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColorParseError::Empty => write!(f, "empty color"),
+            ColorParseError::MissingPrefix(s) => {
+                write!(
+                    f,
+                    "invalid color '{s}': expected it to start with '#' or '0x'"
+                )
+            }
+            ColorParseError::InvalidLength(s) => write!(
+                f,
+                "invalid color '{s}': expected 3 or 6 hex digits after '#' or 6 after '0x'"
+            ),
+            ColorParseError::InvalidDigit(s, c) => {
+                write!(f, "invalid color '{s}': '{c}' is not a hex digit")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ColorParseError {}
+
+impl std::str::FromStr for Rgb {
+    type Err = ColorParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        let (digits, allow_short) = if let Some(digits) = s.strip_prefix('#') {
+            (digits, true)
+        } else if let Some(digits) = s.strip_prefix("0x") {
+            (digits, false)
+        } else if s.is_empty() {
+            return Err(ColorParseError::Empty);
+        } else {
+            return Err(ColorParseError::MissingPrefix(s.into()));
+        };
+
+        if let Some(c) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(ColorParseError::InvalidDigit(s.into(), c));
+        }
+
+        let digit = |i: usize| u8::from_str_radix(&digits[i..i + 1], 16).unwrap();
+
+        match digits.len() {
+            // `#abc` is short for `#aabbcc`
+            3 if allow_short => Ok(Rgb {
+                r: digit(0) * 17,
+                g: digit(1) * 17,
+                b: digit(2) * 17,
+            }),
+            6 => Ok(Rgb {
+                r: digit(0) * 16 + digit(1),
+                g: digit(2) * 16 + digit(3),
+                b: digit(4) * 16 + digit(5),
+            }),
+            _ => Err(ColorParseError::InvalidLength(s.into())),
+        }
+    }
+}
+
+// an sRGB channel as linear light from 0 to 1
+pub(crate) fn linear(channel: u8) -> f64 {
+    let c = channel as f64 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+// linear light back to an sRGB channel, out of range values are clamped
+pub(crate) fn encode(linear: f64) -> u8 {
+    let c = if linear <= 0.0031308 {
+        12.92 * linear
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    };
+
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb { r: 0, g: 0, b: 0 };
+    pub const WHITE: Rgb = Rgb {
+        r: 255,
+        g: 255,
+        b: 255,
+    };
+
+    /// The relative luminance as defined by WCAG 2, from 0 for black to 1 for white.
+    pub fn relative_luminance(self) -> f64 {
+        0.2126 * linear(self.r) + 0.7152 * linear(self.g) + 0.0722 * linear(self.b)
+    }
+
+    /// The WCAG 2 contrast ratio between two colors, from 1 for the same color to 21 for black
+    /// and white. The order of the colors does not matter.
+    pub fn contrast_ratio(self, other: Rgb) -> f64 {
+        let (a, b) = (self.relative_luminance(), other.relative_luminance());
+
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    /// Black or white, whichever is more readable on top of this color.
+    pub fn contrasting_label(self) -> Rgb {
+        // the luminance at which black and white have the same contrast ratio
+        if self.relative_luminance() > 0.179 {
+            Rgb::BLACK
+        } else {
+            Rgb::WHITE
+        }
+    }
+}
+
+/// A color in OKLCH, the polar form of the OKLab perceptual color space. `l` is the perceived
+/// lightness from 0 to 1, `c` the chroma and `h` the hue in degrees. Changing `l` keeps the hue
+/// and colorfulness the eye sees, which is not true for the channels of [`Rgb`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Oklch {
+    pub l: f64,
+    pub c: f64,
+    pub h: f64,
+}
+
+impl From<Rgb> for Oklch {
+    fn from(rgb: Rgb) -> Self {
+        let (r, g, b) = (linear(rgb.r), linear(rgb.g), linear(rgb.b));
+
+        let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+        let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+        let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+
+        let lightness = 0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s;
+        let a = 1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s;
+        let b = 0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s;
+
+        Oklch {
+            l: lightness,
+            c: a.hypot(b),
+            h: b.atan2(a).to_degrees().rem_euclid(360.0),
+        }
+    }
+}
+
+impl Oklch {
+    /// The perceived difference between two colors, the distance in OKLab. Around 0.02 is the
+    /// smallest difference that can be seen.
+    pub fn distance(self, other: Oklch) -> f64 {
+        let (a1, b1) = (
+            self.c * self.h.to_radians().cos(),
+            self.c * self.h.to_radians().sin(),
+        );
+        let (a2, b2) = (
+            other.c * other.h.to_radians().cos(),
+            other.c * other.h.to_radians().sin(),
+        );
+
+        ((self.l - other.l).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+    }
+
+    // the color as linear sRGB, the channels are outside of 0..=1 when it can't be shown
+    fn to_linear(self) -> [f64; 3] {
+        let (a, b) = (
+            self.c * self.h.to_radians().cos(),
+            self.c * self.h.to_radians().sin(),
+        );
+
+        let l = (self.l + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+        let m = (self.l - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+        let s = (self.l - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+
+        [
+            4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+            -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+            -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+        ]
+    }
+
+    /// The closest sRGB color. A color outside of sRGB loses chroma until it fits, so the
+    /// lightness and hue are kept.
+    pub fn to_rgb(self) -> Rgb {
+        let fits = |color: Oklch| {
+            color
+                .to_linear()
+                .iter()
+                .all(|c| (-0.0001..=1.0001).contains(c))
+        };
+
+        let mut color = Oklch {
+            l: self.l.clamp(0.0, 1.0),
+            ..self
+        };
+
+        if !fits(color) {
+            let (mut low, mut high) = (0.0, color.c);
+
+            for _ in 0..24 {
+                let c = (low + high) / 2.0;
+
+                if fits(Oklch { c, ..color }) {
+                    low = c;
+                } else {
+                    high = c;
+                }
+            }
+
+            color.c = low;
+        }
+
+        let [r, g, b] = color.to_linear();
+
+        Rgb {
+            r: encode(r),
+            g: encode(g),
+            b: encode(b),
+        }
+    }
+}
+
+impl std::fmt::Display for Rgb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl TryFrom<String> for Rgb {
+    type Error = ColorParseError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Rgb> for String {
+    fn from(rgb: Rgb) -> Self {
+        rgb.to_string()
+    }
+}
+
+impl std::str::FromStr for CellRgb {
+    type Err = ColorParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "CellForeground" => Ok(CellRgb::CellForeground),
+            "CellBackground" => Ok(CellRgb::CellBackground),
+            s => s.parse().map(CellRgb::Rgb),
+        }
+    }
+}
+
+impl std::fmt::Display for CellRgb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CellRgb::CellForeground => write!(f, "CellForeground"),
+            CellRgb::CellBackground => write!(f, "CellBackground"),
+            CellRgb::Rgb(rgb) => rgb.fmt(f),
+        }
+    }
+}
+
+impl TryFrom<String> for CellRgb {
+    type Error = ColorParseError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<CellRgb> for String {
+    fn from(color: CellRgb) -> Self {
+        color.to_string()
+    }
+}
+
+impl From<Rgb> for CellRgb {
+    fn from(rgb: Rgb) -> Self {
+        CellRgb::Rgb(rgb)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_long_and_short_colors() {
+        let rgb = Rgb {
+            r: 0xaa,
+            g: 0xbb,
+            b: 0xcc,
+        };
+
+        assert_eq!("#aabbcc".parse(), Ok(rgb));
+        assert_eq!("#abc".parse(), Ok(rgb));
+        assert_eq!("0xaabbcc".parse(), Ok(rgb));
+        assert_eq!("#AABBCC".parse(), Ok(rgb));
+        assert_eq!(" #abc ".parse(), Ok(rgb));
+    }
+
+    #[test]
+    fn reject_malformed_colors() {
+        assert_eq!("".parse::<Rgb>(), Err(ColorParseError::Empty));
+        assert_eq!(
+            "aabbcc".parse::<Rgb>(),
+            Err(ColorParseError::MissingPrefix("aabbcc".into()))
+        );
+        assert_eq!(
+            "0xabc".parse::<Rgb>(),
+            Err(ColorParseError::InvalidLength("0xabc".into()))
+        );
+        assert_eq!(
+            "#abcd".parse::<Rgb>(),
+            Err(ColorParseError::InvalidLength("#abcd".into()))
+        );
+        assert_eq!(
+            "#aabbccd".parse::<Rgb>(),
+            Err(ColorParseError::InvalidLength("#aabbccd".into()))
+        );
+        assert_eq!(
+            "#aabbcg".parse::<Rgb>(),
+            Err(ColorParseError::InvalidDigit("#aabbcg".into(), 'g'))
+        );
+    }
+
+    #[test]
+    fn parse_cell_colors() {
+        assert_eq!("CellForeground".parse(), Ok(CellRgb::CellForeground));
+        assert_eq!("CellBackground".parse(), Ok(CellRgb::CellBackground));
+        assert_eq!("#fff".parse(), Ok(CellRgb::Rgb(Rgb::WHITE)));
+        assert_eq!("0x000000".parse(), Ok(CellRgb::Rgb(Rgb::BLACK)));
+        assert_eq!("".parse::<CellRgb>(), Err(ColorParseError::Empty));
+        assert_eq!(
+            "0xfff".parse::<CellRgb>(),
+            Err(ColorParseError::InvalidLength("0xfff".into()))
+        );
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        for color in [
+            CellRgb::CellForeground,
+            CellRgb::CellBackground,
+            CellRgb::Rgb(Rgb {
+                r: 0x12,
+                g: 0x34,
+                b: 0x56,
+            }),
+        ] {
+            let yaml = serde_yaml::to_string(&color).unwrap();
+            assert_eq!(serde_yaml::from_str::<CellRgb>(&yaml).unwrap(), color);
+        }
+
+        assert_eq!(
+            serde_yaml::to_string(&CellRgb::CellForeground).unwrap(),
+            "CellForeground\n"
+        );
+        assert_eq!(
+            serde_yaml::from_str::<Rgb>("'#abc'").unwrap().to_string(),
+            "#aabbcc"
+        );
+        assert!(serde_yaml::from_str::<Rgb>("'#abcd'").is_err());
+    }
+}
@@ -0,0 +1,204 @@
+// How many colors the terminal can show, every color of the UI is brought down to that once a
+// frame is drawn.
+
+use std::env;
+
+use clap::ValueEnum;
+use ratatui::prelude::*;
+
+use crate::terminfo::{color_capabilities, ColorCapabilities};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+pub enum ColorMode {
+    // detect the mode from the environment
+    Auto,
+    #[value(name = "truecolor")]
+    TrueColor,
+    #[value(name = "256")]
+    Ansi256,
+    #[value(name = "16")]
+    Ansi16,
+}
+
+// the 16 colors of xterm, the real colors depend on the terminal's theme
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+// the levels of each channel in the 6x6x6 color cube of xterm-256
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+// the closest of the 240 fixed xterm-256 colors, the first 16 follow the terminal's theme so
+// they are never picked
+fn to_xterm256(rgb: (u8, u8, u8)) -> u8 {
+    let level = |c: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - c as i32).abs())
+            .unwrap()
+    };
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube = (CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]);
+
+    // the grayscale ramp, from 8 to 238 in steps of 10
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let gray_index = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray_level = 8 + 10 * gray_index;
+
+    if distance(rgb, (gray_level, gray_level, gray_level)) < distance(rgb, cube) {
+        232 + gray_index
+    } else {
+        16 + 36 * r as u8 + 6 * g as u8 + b as u8
+    }
+}
+
+fn to_ansi16(rgb: (u8, u8, u8)) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, ansi)| distance(rgb, *ansi))
+        .unwrap()
+        .0
+}
+
+impl ColorMode {
+    // the mode of the terminal, from $COLORTERM, the terminfo entry of $TERM and the name in
+    // $TERM when there is no entry
+    pub fn detect() -> ColorMode {
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        let term = env::var("TERM").unwrap_or_default();
+
+        // alacritty supports 24-bit colors, but its terminfo entry only says 256 colors
+        if matches!(colorterm.as_str(), "truecolor" | "24bit")
+            || term.ends_with("-direct")
+            || term == "alacritty"
+        {
+            return ColorMode::TrueColor;
+        }
+
+        match color_capabilities(&term) {
+            Some(capabilities) => ColorMode::from_capabilities(capabilities),
+            None if term.contains("256color") => ColorMode::Ansi256,
+            None => ColorMode::Ansi16,
+        }
+    }
+
+    // the mode of a terminfo entry
+    fn from_capabilities(capabilities: ColorCapabilities) -> ColorMode {
+        match capabilities.colors.unwrap_or(0) {
+            _ if capabilities.direct => ColorMode::TrueColor,
+            0x1000000.. => ColorMode::TrueColor,
+            256.. => ColorMode::Ansi256,
+            _ => ColorMode::Ansi16,
+        }
+    }
+
+    // `Auto` replaced with the detected mode
+    pub fn resolve(self) -> ColorMode {
+        match self {
+            ColorMode::Auto => ColorMode::detect(),
+            mode => mode,
+        }
+    }
+
+    // the closest color the terminal can show
+    pub fn quantize(self, color: Color) -> Color {
+        let Color::Rgb(r, g, b) = color else {
+            return color;
+        };
+
+        match self {
+            ColorMode::Auto | ColorMode::TrueColor => color,
+            ColorMode::Ansi256 => Color::Indexed(to_xterm256((r, g, b))),
+            ColorMode::Ansi16 => to_ansi16((r, g, b)),
+        }
+    }
+
+    // quantize every cell of `area`
+    pub fn quantize_buffer(self, area: Rect, buf: &mut Buffer) {
+        if matches!(self, ColorMode::Auto | ColorMode::TrueColor) {
+            return;
+        }
+
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let cell = buf.get_mut(x, y);
+                cell.fg = self.quantize(cell.fg);
+                cell.bg = self.quantize(cell.bg);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xterm256_cube_and_grays() {
+        assert_eq!(to_xterm256((0, 0, 0)), 16);
+        assert_eq!(to_xterm256((255, 255, 255)), 231);
+        assert_eq!(to_xterm256((255, 0, 0)), 196);
+        assert_eq!(to_xterm256((95, 135, 175)), 16 + 36 + 6 * 2 + 3);
+        // grays between the cube levels go to the ramp
+        assert_eq!(to_xterm256((128, 128, 128)), 244);
+        assert_eq!(to_xterm256((8, 8, 8)), 232);
+        assert_eq!(to_xterm256((238, 238, 238)), 255);
+    }
+
+    #[test]
+    fn ansi16_closest() {
+        assert_eq!(to_ansi16((10, 10, 10)), Color::Black);
+        assert_eq!(to_ansi16((250, 10, 10)), Color::LightRed);
+        assert_eq!(to_ansi16((200, 10, 10)), Color::Red);
+        assert_eq!(to_ansi16((120, 120, 130)), Color::DarkGray);
+    }
+
+    #[test]
+    fn quantize_only_rgb_colors() {
+        assert_eq!(
+            ColorMode::Ansi256.quantize(Color::Rgb(255, 0, 0)),
+            Color::Indexed(196)
+        );
+        assert_eq!(
+            ColorMode::Ansi16.quantize(Color::Rgb(0, 0, 0)),
+            Color::Black
+        );
+        assert_eq!(
+            ColorMode::TrueColor.quantize(Color::Rgb(1, 2, 3)),
+            Color::Rgb(1, 2, 3)
+        );
+        assert_eq!(ColorMode::Ansi16.quantize(Color::Reset), Color::Reset);
+    }
+
+    #[test]
+    fn mode_of_capabilities() {
+        let mode =
+            |colors, direct| ColorMode::from_capabilities(ColorCapabilities { colors, direct });
+
+        assert_eq!(mode(Some(256), true), ColorMode::TrueColor);
+        assert_eq!(mode(Some(0x1000000), false), ColorMode::TrueColor);
+        assert_eq!(mode(Some(256), false), ColorMode::Ansi256);
+        assert_eq!(mode(Some(88), false), ColorMode::Ansi16);
+        assert_eq!(mode(None, false), ColorMode::Ansi16);
+    }
+}
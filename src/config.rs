@@ -0,0 +1,994 @@
+//! Finding the alacritty config and writing themes to it.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+
+use crate::error::{env_var, read_file, write_file, Error};
+use crate::migrate::{resolve_import, resolve_path};
+
+/// The format of a config or theme file.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ConfigFormat {
+    Yaml,
+    Toml,
+}
+
+impl ConfigFormat {
+    /// Pick the format from the file extension, yaml is the legacy default.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => ConfigFormat::Toml,
+            _ => ConfigFormat::Yaml,
+        }
+    }
+
+    /// Deserialize `contents`, `path` is only used for the error.
+    pub fn parse<T: DeserializeOwned>(self, path: &Path, contents: &str) -> Result<T, Error> {
+        let result = match self {
+            ConfigFormat::Yaml => serde_yaml::from_str(contents).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::from_str(contents).map_err(|e| e.to_string()),
+        };
+
+        result.map_err(|message| Error::Parse {
+            path: path.to_path_buf(),
+            message,
+        })
+    }
+}
+
+/// Names of the main config file, alacritty prefers toml since v0.13.
+pub const CONFIG_FILE_NAMES: [&str; 2] = ["alacritty.toml", "alacritty.yml"];
+
+// an xdg base dir variable, which is ignored when it is empty or not an absolute path
+fn xdg_dir(name: &str) -> Option<PathBuf> {
+    env::var_os(name)
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+}
+
+/// The user's config dir, `$XDG_CONFIG_HOME` falling back to `~/.config`.
+pub fn config_home() -> Result<PathBuf, Error> {
+    match xdg_dir("XDG_CONFIG_HOME") {
+        Some(dir) => Ok(dir),
+        None => Ok(PathBuf::from(env_var("HOME")?).join(".config")),
+    }
+}
+
+/// Every path alacritty looks for its config at, in the order it checks them. Each location is
+/// checked for `alacritty.toml` before any of them is checked for the legacy `alacritty.yml`.
+pub fn config_file_candidates() -> Vec<PathBuf> {
+    let home = env::var_os("HOME").map(PathBuf::from);
+    let xdg_config_home = config_home().ok();
+    let xdg_config_dirs: Vec<PathBuf> = match env::var_os("XDG_CONFIG_DIRS") {
+        Some(dirs) if !dirs.is_empty() => env::split_paths(&dirs)
+            .filter(|dir| dir.is_absolute())
+            .collect(),
+        _ => vec![PathBuf::from("/etc/xdg")],
+    };
+
+    let mut candidates: Vec<PathBuf> = Vec::new();
+
+    for name in CONFIG_FILE_NAMES {
+        let mut paths: Vec<PathBuf> = Vec::new();
+
+        if let Some(xdg_config_home) = &xdg_config_home {
+            paths.push(xdg_config_home.join("alacritty").join(name));
+            paths.push(xdg_config_home.join(name));
+        }
+        if let Some(home) = &home {
+            paths.push(home.join(".config").join("alacritty").join(name));
+        }
+        for dir in &xdg_config_dirs {
+            paths.push(dir.join("alacritty").join(name));
+        }
+        if let Some(home) = &home {
+            paths.push(home.join(format!(".{name}")));
+        }
+
+        // `~/.config/alacritty` is also the xdg dir when `XDG_CONFIG_HOME` is not set
+        for path in paths {
+            if !candidates.contains(&path) {
+                candidates.push(path);
+            }
+        }
+    }
+
+    candidates
+}
+
+/// Find the alacritty config file of the current user, the same way alacritty does.
+pub fn find_alacritty_config_file() -> Result<PathBuf, Error> {
+    let candidates = config_file_candidates();
+
+    match candidates.iter().find(|path| path.is_file()) {
+        Some(path) => Ok(path.clone()),
+        None => Err(Error::ConfigNotFound { tried: candidates }),
+    }
+}
+
+/// Backup the main config file before doing any changes, `alacritty.toml` is copied to
+/// `alacritty-backup.toml`. An existing backup is never overwritten, the path of the backup is
+/// returned when one was made.
+pub fn backup_cfg_file(file: &Path) -> Result<Option<PathBuf>, Error> {
+    // a config without an extension gets a backup without one
+    let mut new_name = file.file_stem().unwrap_or_default().to_os_string();
+    new_name.push("-backup");
+    if let Some(extension) = file.extension() {
+        new_name.push(".");
+        new_name.push(extension);
+    }
+    let backup_file = file.with_file_name(new_name);
+
+    if backup_file.exists() {
+        return Ok(None);
+    }
+
+    fs::copy(file, &backup_file).map_err(|source| Error::Io {
+        action: "backup",
+        path: file.to_path_buf(),
+        source,
+    })?;
+
+    Ok(Some(backup_file))
+}
+
+/// Convert a yaml value to toml, toml has no null so those keys are dropped.
+pub fn yaml_to_toml(value: &serde_yaml::Value) -> Option<toml::Value> {
+    use serde_yaml::Value;
+
+    match value {
+        Value::Null => None,
+        Value::Bool(b) => Some(toml::Value::Boolean(*b)),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Some(toml::Value::Integer(i)),
+            None => n.as_f64().map(toml::Value::Float),
+        },
+        Value::String(s) => Some(toml::Value::String(s.clone())),
+        Value::Sequence(seq) => Some(toml::Value::Array(
+            seq.iter().filter_map(yaml_to_toml).collect(),
+        )),
+        Value::Mapping(map) => {
+            let mut table = toml::Table::new();
+
+            for (key, value) in map {
+                let key = match key {
+                    Value::String(s) => s.clone(),
+                    other => serde_yaml::to_string(other).unwrap().trim_end().to_string(),
+                };
+
+                if let Some(value) = yaml_to_toml(value) {
+                    table.insert(key, value);
+                }
+            }
+
+            Some(toml::Value::Table(table))
+        }
+        Value::Tagged(tagged) => yaml_to_toml(&tagged.value),
+    }
+}
+
+/// Read the `colors` of a theme file. Themes can be yaml or toml so they are read into a yaml
+/// value which can be applied to either config format.
+pub fn read_theme_colors(theme_path: &Path) -> Result<serde_yaml::Value, Error> {
+    let theme_file_contents = read_file(theme_path)?;
+
+    let new_theme_color: serde_yaml::Value = match ConfigFormat::from_path(theme_path) {
+        ConfigFormat::Yaml => ConfigFormat::Yaml.parse(theme_path, &theme_file_contents)?,
+        ConfigFormat::Toml => {
+            let theme: toml::Value = ConfigFormat::Toml.parse(theme_path, &theme_file_contents)?;
+            serde_yaml::to_value(theme).map_err(|e| Error::Parse {
+                path: theme_path.to_path_buf(),
+                message: e.to_string(),
+            })?
+        }
+    };
+
+    // a file without colors would remove the colors of the config
+    match new_theme_color.get("colors") {
+        Some(colors) if !colors.is_null() => Ok(colors.clone()),
+        _ => Err(Error::Parse {
+            path: theme_path.to_path_buf(),
+            message: "missing `colors`".to_string(),
+        }),
+    }
+}
+
+// call `f` on every table in `item`, including nested tables and arrays of tables
+fn for_each_table(item: &mut toml_edit::Item, f: &mut impl FnMut(&mut toml_edit::Table)) {
+    match item {
+        toml_edit::Item::Table(table) => {
+            f(table);
+
+            for (_, item) in table.iter_mut() {
+                for_each_table(item, f);
+            }
+        }
+        toml_edit::Item::ArrayOfTables(tables) => {
+            for table in tables.iter_mut() {
+                f(table);
+
+                for (_, item) in table.iter_mut() {
+                    for_each_table(item, f);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+// a table that is written with its own `[header]`
+fn has_header(table: &toml_edit::Table) -> bool {
+    !table.is_implicit() && !table.is_dotted()
+}
+
+// a `key = value` line, or lines of dotted keys, in the table it belongs to
+fn is_line(item: &toml_edit::Item) -> bool {
+    item.is_value() || item.as_table().is_some_and(|table| table.is_dotted())
+}
+
+// call `f` with the decor of the first `key = value` line written for `table`, the comments above
+// it are in its prefix
+fn with_first_line_decor(table: &mut toml_edit::Table, f: impl FnOnce(&mut toml_edit::Decor)) {
+    let Some(key) = table
+        .iter()
+        .find(|(_, item)| is_line(item))
+        .map(|(key, _)| key.to_string())
+    else {
+        return;
+    };
+
+    if table[key.as_str()].is_value() {
+        if let Some(mut key) = table.key_mut(&key) {
+            f(key.leaf_decor_mut());
+        }
+    } else if let Some(table) = table[key.as_str()].as_table_mut() {
+        with_first_line_decor(table, f);
+    }
+}
+
+// put `prefix` above the table that is written first
+fn prepend_to_first_table(doc: &mut toml_edit::DocumentMut, prefix: &str) {
+    let mut first: Option<isize> = None;
+    for (_, item) in doc.iter_mut() {
+        for_each_table(item, &mut |table| {
+            if let Some(position) = table.position().filter(|_| has_header(table)) {
+                first = Some(first.map_or(position, |first| first.min(position)));
+            }
+        });
+    }
+
+    let mut done = false;
+    for (_, item) in doc.iter_mut() {
+        for_each_table(item, &mut |table| {
+            if !done && has_header(table) && table.position() == first {
+                let old = table
+                    .decor()
+                    .prefix()
+                    .and_then(|old| old.as_str())
+                    .unwrap_or_default()
+                    .to_string();
+                table.decor_mut().set_prefix(format!("{prefix}{old}"));
+                done = true;
+            }
+        });
+    }
+}
+
+// write the tables of `item` as dotted keys, arrays of tables become inline arrays
+fn make_dotted(item: &mut toml_edit::Item) {
+    match item {
+        toml_edit::Item::Table(table) => {
+            table.set_dotted(true);
+
+            for (_, item) in table.iter_mut() {
+                make_dotted(item);
+            }
+        }
+        toml_edit::Item::ArrayOfTables(_) => {
+            let tables = std::mem::take(item);
+            *item = tables
+                .into_value()
+                .map(toml_edit::Item::Value)
+                .unwrap_or_else(|tables| tables);
+        }
+        _ => {}
+    }
+}
+
+// replace the `colors` table, everything else in the document keeps its formatting
+fn set_toml_colors(
+    path: &Path,
+    contents: &str,
+    colors: &serde_yaml::Value,
+) -> Result<String, Error> {
+    let mut doc: toml_edit::DocumentMut = contents.parse().map_err(|e| Error::Parse {
+        path: path.to_path_buf(),
+        message: format!("{e}"),
+    })?;
+
+    // the comments at the start of the file stay there when the colors are its first lines
+    let first_line_is_colors = doc
+        .iter()
+        .find(|(_, item)| is_line(item))
+        .is_some_and(|(key, _)| key == "colors");
+    let mut file_prefix = None;
+    if first_line_is_colors {
+        with_first_line_decor(doc.as_table_mut(), |decor| {
+            file_prefix = decor.prefix().cloned();
+            decor.set_prefix("");
+        });
+    }
+
+    let Some(colors) = yaml_to_toml(colors) else {
+        doc.remove("colors");
+        return Ok(doc.to_string());
+    };
+
+    let mut new_doc = toml::Table::new();
+    new_doc.insert("colors".into(), colors);
+    let new_doc = toml::to_string(&new_doc).map_err(|e| Error::Serialize {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    })?;
+    let mut new_doc: toml_edit::DocumentMut = new_doc.parse().unwrap();
+    let mut new_colors = new_doc.remove("colors").unwrap();
+
+    // find where the old colors tables start and the comments above the first one
+    let mut old_header: Option<(isize, toml_edit::Decor)> = None;
+    if let Some(old_colors) = doc.get_mut("colors") {
+        for_each_table(old_colors, &mut |table| {
+            if let Some(position) = table.position().filter(|_| has_header(table)) {
+                if old_header
+                    .as_ref()
+                    .is_none_or(|(first, _)| position < *first)
+                {
+                    old_header = Some((position, table.decor().clone()));
+                }
+            }
+        });
+    }
+    let old_is_dotted = doc
+        .get("colors")
+        .and_then(|colors| colors.as_table())
+        .is_some_and(|colors| colors.is_dotted())
+        && old_header.is_none();
+
+    if old_is_dotted {
+        // written as dotted keys in the place of the old ones
+        make_dotted(&mut new_colors);
+        doc["colors"] = new_colors;
+    } else {
+        // every new table goes where the first old one was, or after all other tables
+        let position = match &old_header {
+            Some((position, _)) => *position,
+            None => {
+                let mut last = 0;
+                for (_, item) in doc.iter_mut() {
+                    for_each_table(item, &mut |table| {
+                        last = last.max(table.position().unwrap_or(0));
+                    });
+                }
+                last + 1
+            }
+        };
+
+        let mut is_first = true;
+        for_each_table(&mut new_colors, &mut |table| {
+            table.set_position(Some(position));
+
+            if is_first && has_header(table) {
+                match &old_header {
+                    Some((_, decor)) => *table.decor_mut() = decor.clone(),
+                    None if !contents.trim().is_empty() => table.decor_mut().set_prefix("\n"),
+                    None => {}
+                }
+                is_first = false;
+            }
+        });
+
+        doc.remove("colors");
+        doc.insert("colors", new_colors);
+    }
+
+    if let Some(prefix) = file_prefix {
+        let prefix = prefix.as_str().unwrap_or_default().to_string();
+        let mut placed = false;
+
+        with_first_line_decor(doc.as_table_mut(), |decor| {
+            decor.set_prefix(prefix.clone());
+            placed = true;
+        });
+
+        // no line is left before the tables, the comments go above the first table
+        if !placed {
+            prepend_to_first_table(&mut doc, &prefix);
+        }
+    }
+
+    Ok(doc.to_string())
+}
+
+// replace the top level `colors:` block line by line, everything else is kept as is
+fn set_yaml_colors(
+    path: &Path,
+    contents: &str,
+    colors: &serde_yaml::Value,
+) -> Result<NewContents, Error> {
+    let serialize_error = |e: serde_yaml::Error| Error::Serialize {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    };
+
+    let new_block = if colors.is_null() {
+        String::new()
+    } else {
+        let mut new_doc = serde_yaml::Mapping::new();
+        new_doc.insert("colors".into(), colors.clone());
+        serde_yaml::to_string(&new_doc).map_err(serialize_error)?
+    };
+
+    let lines: Vec<&str> = contents.split_inclusive('\n').collect();
+    let is_top_level_key =
+        |line: &str| !line.trim().is_empty() && !line.starts_with([' ', '\t', '#']);
+    let is_colors_key = |line: &str| {
+        line.strip_prefix("colors:")
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+    };
+
+    let new_contents = match lines.iter().position(|line| is_colors_key(line)) {
+        Some(start) => {
+            let mut end = start + 1;
+            while end < lines.len() && !is_top_level_key(lines[end]) {
+                end += 1;
+            }
+
+            // blank lines and comments right above the next key belong to that key
+            while end > start + 1
+                && (lines[end - 1].trim().is_empty() || lines[end - 1].starts_with('#'))
+            {
+                end -= 1;
+            }
+
+            format!(
+                "{}{}{}",
+                lines[..start].concat(),
+                new_block,
+                lines[end..].concat()
+            )
+        }
+        None => {
+            let mut new_contents = contents.to_string();
+
+            if !new_contents.is_empty() && !new_contents.ends_with('\n') {
+                new_contents.push('\n');
+            }
+
+            new_contents + &new_block
+        }
+    };
+
+    // the old block could define anchors that are used elsewhere, in that case the whole config
+    // is rewritten
+    if serde_yaml::from_str::<serde_yaml::Value>(&new_contents).is_err() {
+        let mut color: serde_yaml::Value = ConfigFormat::Yaml.parse(path, contents)?;
+        color["colors"] = colors.clone();
+
+        return Ok(NewContents {
+            contents: serde_yaml::to_string(&color).map_err(serialize_error)?,
+            rewritten: true,
+        });
+    }
+
+    Ok(NewContents {
+        contents: new_contents,
+        rewritten: false,
+    })
+}
+
+/// The contents of a file with new colors, see [`set_colors`].
+#[derive(Debug)]
+pub struct NewContents {
+    pub contents: String,
+    /// The yaml file was written again as a whole and lost its comments and formatting, because
+    /// the old colors define anchors that the rest of the file uses.
+    pub rewritten: bool,
+}
+
+/// Replace the `colors` section of the contents of the config file at `path`, everything else
+/// keeps its formatting and comments unless [`NewContents::rewritten`] is set.
+pub fn set_colors(
+    path: &Path,
+    contents: &str,
+    colors: &serde_yaml::Value,
+) -> Result<NewContents, Error> {
+    match ConfigFormat::from_path(path) {
+        ConfigFormat::Yaml => set_yaml_colors(path, contents, colors),
+        ConfigFormat::Toml => Ok(NewContents {
+            contents: set_toml_colors(path, contents, colors)?,
+            rewritten: false,
+        }),
+    }
+}
+
+/// How a theme is written to the config.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ApplyMode {
+    /// Replace the colors section of the main config.
+    Rewrite,
+    /// Write the colors to a managed file imported by the main config.
+    Import,
+}
+
+/// File name, without extension, of the theme file managed in [`ApplyMode::Import`].
+pub const MANAGED_THEME_FILE_NAME: &str = "current-theme";
+
+const MANAGED_THEME_FILE_HEADER: &str =
+    "# Managed by alacritty-theme-switcher, changes to this file will be overwritten\n";
+
+/// The managed theme file lives next to the main config and uses the same format.
+pub fn get_managed_theme_file(file_path: &Path) -> PathBuf {
+    let extension = file_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("yml");
+
+    file_path.with_file_name(format!("{MANAGED_THEME_FILE_NAME}.{extension}"))
+}
+
+/// What [`apply_theme`] changed besides writing the colors.
+#[derive(Default, Debug)]
+pub struct Applied {
+    /// The backup made before the config was changed for the first time.
+    pub backup: Option<PathBuf>,
+    /// The managed theme file was added to the imports of the config.
+    pub import_added: bool,
+    /// The config has its own colors, which take precedence over the imported theme.
+    pub colors_overridden: bool,
+    /// The yaml config was written again as a whole, see [`NewContents::rewritten`].
+    pub rewritten: bool,
+}
+
+// make sure the main config imports `import_path`, the config is only touched when the import
+// is missing
+fn ensure_import(path: &Path, import_file: &Path) -> Result<Applied, Error> {
+    let import_path = import_file.to_string_lossy();
+    let import_path = import_path.as_ref();
+    // an import written by hand, or by an older version as a relative path, counts too
+    let is_imported = |import: Option<&str>| {
+        import.is_some_and(|import| resolve_import(path, import) == import_file)
+    };
+    let mut applied = Applied::default();
+    let alacritty_cfg_contents = read_file(path)?;
+    let import_error = |message: String| Error::Parse {
+        path: path.to_path_buf(),
+        message,
+    };
+
+    let new_cfg_file = match ConfigFormat::from_path(path) {
+        ConfigFormat::Toml => {
+            let mut doc: toml_edit::DocumentMut = alacritty_cfg_contents
+                .parse()
+                .map_err(|e| import_error(format!("{e}")))?;
+
+            applied.colors_overridden = doc.get("colors").is_some();
+
+            // alacritty >= 0.14 reads `general.import`, older versions the top level `import`
+            let imports = if doc.get("general").is_some_and(|general| general.is_table()) {
+                doc["general"]
+                    .as_table_mut()
+                    .unwrap()
+                    .entry("import")
+                    .or_insert(toml_edit::value(toml_edit::Array::new()))
+            } else {
+                doc.entry("import")
+                    .or_insert(toml_edit::value(toml_edit::Array::new()))
+            };
+
+            let Some(imports) = imports.as_array_mut() else {
+                return Err(import_error(format!(
+                    "`import` is not an array, add '{import_path}' to it"
+                )));
+            };
+
+            if imports.iter().any(|import| is_imported(import.as_str())) {
+                return Ok(applied);
+            }
+
+            imports.push(import_path);
+
+            doc.to_string()
+        }
+        ConfigFormat::Yaml => {
+            let cfg: serde_yaml::Value = ConfigFormat::Yaml.parse(path, &alacritty_cfg_contents)?;
+
+            applied.colors_overridden = cfg.get("colors").is_some();
+
+            // quoted by yaml's rules, a path with `: ` or `#` in it needs quotes there
+            let import_item =
+                serde_yaml::to_string(import_path).map_err(|e| import_error(format!("{e}")))?;
+            let import_item = format!("  - {}", import_item.trim_end());
+
+            match cfg.get("import") {
+                None => format!("import:\n{import_item}\n{alacritty_cfg_contents}"),
+                Some(imports) => {
+                    let imports = imports.as_sequence().cloned().unwrap_or_default();
+
+                    if imports.iter().any(|import| is_imported(import.as_str())) {
+                        return Ok(applied);
+                    }
+
+                    // only block style lists can be extended without rewriting the file
+                    let lines: Vec<&str> = alacritty_cfg_contents.lines().collect();
+                    let Some(import_line) =
+                        lines.iter().position(|line| line.trim_end() == "import:")
+                    else {
+                        return Err(import_error(format!(
+                            "only a block style `import` list can be extended, add '{import_path}' to it"
+                        )));
+                    };
+
+                    // imports later in the list take precedence, so add it after the last item
+                    let mut end = import_line + 1;
+                    while end < lines.len()
+                        && (lines[end].starts_with([' ', '-']) || lines[end].trim().is_empty())
+                    {
+                        end += 1;
+                    }
+                    while lines[end - 1].trim().is_empty() {
+                        end -= 1;
+                    }
+
+                    let mut new_lines: Vec<String> =
+                        lines.iter().map(|line| line.to_string()).collect();
+                    new_lines.insert(end, import_item);
+
+                    new_lines.join("\n") + "\n"
+                }
+            }
+        }
+    };
+
+    applied.backup = backup_cfg_file(path)?;
+    write_file(path, &new_cfg_file)?;
+    applied.import_added = true;
+
+    Ok(applied)
+}
+
+/// Write the colors of the theme at `theme_path` to the config at `file_path`.
+pub fn apply_theme(file_path: &Path, theme_path: &Path, mode: ApplyMode) -> Result<Applied, Error> {
+    // a theme without colors fails here, before the config is touched
+    let colors = read_theme_colors(theme_path)?;
+
+    match mode {
+        ApplyMode::Rewrite => {
+            let alacritty_cfg_contents = read_file(file_path)?;
+            let new_cfg_file = set_colors(file_path, &alacritty_cfg_contents, &colors)?;
+
+            write_file(file_path, &new_cfg_file.contents)?;
+
+            Ok(Applied {
+                rewritten: new_cfg_file.rewritten,
+                ..Applied::default()
+            })
+        }
+        ApplyMode::Import => {
+            // imported by its absolute path, alacritty versions differ in how they resolve
+            // relative imports
+            let managed_file = resolve_path(&get_managed_theme_file(file_path));
+
+            let applied = ensure_import(file_path, &managed_file)?;
+
+            let new_file = set_colors(&managed_file, "", &colors)?;
+            write_file(
+                &managed_file,
+                &(MANAGED_THEME_FILE_HEADER.to_string() + &new_file.contents),
+            )?;
+
+            Ok(applied)
+        }
+    }
+}
+
+/// The files [`apply_theme`] writes to as they were when the snapshot was taken, so a previewed
+/// theme can be undone.
+#[derive(Debug)]
+pub struct ConfigSnapshot {
+    // `None` for a file that did not exist
+    files: Vec<(PathBuf, Option<String>)>,
+}
+
+impl ConfigSnapshot {
+    /// Remember the files that applying a theme to `file_path` with `mode` would change.
+    pub fn take(file_path: &Path, mode: ApplyMode) -> Result<Self, Error> {
+        let mut paths = vec![file_path.to_path_buf()];
+
+        if mode == ApplyMode::Import {
+            paths.push(get_managed_theme_file(file_path));
+        }
+
+        let mut files = Vec::new();
+        for path in paths {
+            let contents = if path.exists() {
+                Some(read_file(&path)?)
+            } else {
+                None
+            };
+
+            files.push((path, contents));
+        }
+
+        Ok(Self { files })
+    }
+
+    /// Write the files back, files that are unchanged are not touched so alacritty does not
+    /// reload them for nothing.
+    pub fn restore(&self) -> Result<(), Error> {
+        for (path, contents) in &self.files {
+            match contents {
+                Some(contents) if fs::read_to_string(path).ok().as_ref() != Some(contents) => {
+                    write_file(path, contents)?;
+                }
+                Some(_) => {}
+                None if path.exists() => {
+                    fs::remove_file(path).map_err(|source| Error::Io {
+                        action: "remove",
+                        path: path.clone(),
+                        source,
+                    })?;
+                }
+                None => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_colors() -> serde_yaml::Value {
+        serde_yaml::from_str("primary:\n  background: '#282828'\n  foreground: '#ebdbb2'\n")
+            .unwrap()
+    }
+
+    fn set(name: &str, contents: &str) -> NewContents {
+        set_colors(Path::new(name), contents, &new_colors()).unwrap()
+    }
+
+    #[test]
+    fn toml_tables_keep_their_place_and_comments() {
+        let contents = "# top\n[window]\nopacity = 0.9\n\n# colors\n[colors.primary]\nbackground = \"#000000\"\n\n[env]\nTERM = \"x\"\n\n[colors.normal]\nblack = \"#111111\"\n";
+
+        assert_eq!(
+            set("alacritty.toml", contents).contents,
+            "# top\n[window]\nopacity = 0.9\n\n# colors\n[colors.primary]\nbackground = \"#282828\"\nforeground = \"#ebdbb2\"\n\n[env]\nTERM = \"x\"\n"
+        );
+    }
+
+    #[test]
+    fn toml_colors_are_added_at_the_end() {
+        let contents = "# top\n[window]\nopacity = 0.9\n";
+
+        assert_eq!(
+            set("alacritty.toml", contents).contents,
+            "# top\n[window]\nopacity = 0.9\n\n[colors.primary]\nbackground = \"#282828\"\nforeground = \"#ebdbb2\"\n"
+        );
+    }
+
+    #[test]
+    fn toml_comments_at_the_start_stay_there() {
+        let contents =
+            "# top\n[colors.primary]\nbackground = \"#000000\"\n\n[window]\nopacity = 0.9\n";
+
+        assert_eq!(
+            set("alacritty.toml", contents).contents,
+            "# top\n[colors.primary]\nbackground = \"#282828\"\nforeground = \"#ebdbb2\"\n\n[window]\nopacity = 0.9\n"
+        );
+    }
+
+    #[test]
+    fn toml_dotted_colors_stay_dotted() {
+        let contents = "# top\ncolors.primary.background = \"#000000\"\ncolors.primary.foreground = \"#ffffff\"\n\n# window stuff\n[window]\nopacity = 0.9\n";
+
+        assert_eq!(
+            set("alacritty.toml", contents).contents,
+            "# top\ncolors.primary.background = \"#282828\"\ncolors.primary.foreground = \"#ebdbb2\"\n\n# window stuff\n[window]\nopacity = 0.9\n"
+        );
+    }
+
+    #[test]
+    fn toml_mixed_dotted_colors_become_one_table() {
+        let contents = "# top\ncolors.primary.background = \"#000000\"\nfont.size = 11\n\n# window stuff\n[window]\nopacity = 0.9\n\n[colors.normal]\nblack = \"#111111\"\n";
+
+        assert_eq!(
+            set("alacritty.toml", contents).contents,
+            "# top\nfont.size = 11\n\n# window stuff\n[window]\nopacity = 0.9\n\n[colors.primary]\nbackground = \"#282828\"\nforeground = \"#ebdbb2\"\n"
+        );
+    }
+
+    #[test]
+    fn yaml_keeps_the_rest_of_the_file() {
+        let contents = "# top\nfont:\n  size: 11 # big\n\ncolors:\n  primary:\n    background: '#000000'\n\n# window stuff\nwindow:\n  opacity: 0.9\n";
+        let new = set("alacritty.yml", contents);
+
+        assert!(!new.rewritten);
+        assert_eq!(
+            new.contents,
+            "# top\nfont:\n  size: 11 # big\n\ncolors:\n  primary:\n    background: '#282828'\n    foreground: '#ebdbb2'\n\n# window stuff\nwindow:\n  opacity: 0.9\n"
+        );
+    }
+
+    #[test]
+    fn yaml_is_rewritten_when_colors_define_anchors() {
+        let contents =
+            "colors:\n  primary:\n    background: &bg '#000000'\n# used below\nhints:\n  background: *bg\n";
+        let new = set("alacritty.yml", contents);
+
+        assert!(new.rewritten);
+        let value: serde_yaml::Value = serde_yaml::from_str(&new.contents).unwrap();
+        assert_eq!(value["colors"], new_colors());
+        assert_eq!(value["hints"]["background"], "#000000");
+    }
+
+    // a fresh dir for each test, tests run in parallel
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("ats-config-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn apply_partial_theme() {
+        let dir = temp_dir("partial");
+        let config = dir.join("alacritty.toml");
+        let theme = dir.join("partial.toml");
+        fs::write(&config, "[window]\nopacity = 0.9\n").unwrap();
+        fs::write(&theme, "[colors.primary]\nbackground = \"#282828\"\n").unwrap();
+
+        apply_theme(&config, &theme, ApplyMode::Rewrite).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&config).unwrap(),
+            "[window]\nopacity = 0.9\n\n[colors.primary]\nbackground = \"#282828\"\n"
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn theme_without_colors_is_rejected() {
+        let dir = temp_dir("no-colors");
+        let config = dir.join("alacritty.toml");
+        let theme = dir.join("empty.toml");
+        fs::write(&config, "[colors.primary]\nbackground = \"#000000\"\n").unwrap();
+        fs::write(&theme, "[window]\nopacity = 1\n").unwrap();
+
+        assert!(apply_theme(&config, &theme, ApplyMode::Rewrite).is_err());
+        assert_eq!(
+            fs::read_to_string(&config).unwrap(),
+            "[colors.primary]\nbackground = \"#000000\"\n"
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    // the config and the file imported by it, in a fresh dir
+    fn import_into(name: &str, file_name: &str, contents: &str) -> (PathBuf, PathBuf, Applied) {
+        let dir = temp_dir(name);
+        let config = dir.join(file_name);
+        let import_file = dir.join("themes: dark #1.toml");
+        fs::write(&config, contents).unwrap();
+
+        let applied = ensure_import(&config, &import_file).unwrap();
+
+        (dir, config, applied)
+    }
+
+    #[test]
+    fn toml_import_goes_to_general() {
+        let (dir, config, applied) = import_into(
+            "import-general",
+            "alacritty.toml",
+            "[general]\nlive_config_reload = true\n",
+        );
+        let import_file = dir.join("themes: dark #1.toml");
+
+        assert!(applied.import_added);
+        let value: toml::Value = toml::from_str(&fs::read_to_string(&config).unwrap()).unwrap();
+        assert_eq!(
+            value["general"]["import"][0].as_str(),
+            Some(import_file.to_str().unwrap())
+        );
+        assert!(value.get("import").is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn toml_import_without_general() {
+        let (dir, config, applied) =
+            import_into("import-top", "alacritty.toml", "[window]\nopacity = 0.9\n");
+        let import_file = dir.join("themes: dark #1.toml");
+
+        assert!(applied.import_added);
+        assert!(applied.backup.is_some());
+        let value: toml::Value = toml::from_str(&fs::read_to_string(&config).unwrap()).unwrap();
+        assert_eq!(
+            value["import"][0].as_str(),
+            Some(import_file.to_str().unwrap())
+        );
+        assert_eq!(value["window"]["opacity"].as_float(), Some(0.9));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn yaml_import_is_added_at_the_top() {
+        let (dir, config, applied) = import_into(
+            "import-yaml-new",
+            "alacritty.yml",
+            "# top\nwindow:\n  opacity: 0.9\n",
+        );
+        let import_file = dir.join("themes: dark #1.toml");
+
+        assert!(applied.import_added);
+        let contents = fs::read_to_string(&config).unwrap();
+        assert!(contents.ends_with("\n# top\nwindow:\n  opacity: 0.9\n"));
+        let value: serde_yaml::Value = serde_yaml::from_str(&contents).unwrap();
+        assert_eq!(
+            value["import"][0].as_str(),
+            Some(import_file.to_str().unwrap())
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn yaml_import_is_added_after_the_last_item() {
+        let (dir, config, applied) = import_into(
+            "import-yaml-list",
+            "alacritty.yml",
+            "import:\n  - ~/fonts.yml\n  - ~/keys.yml\n\n# window stuff\nwindow:\n  opacity: 0.9\n",
+        );
+        let import_file = dir.join("themes: dark #1.toml");
+
+        assert!(applied.import_added);
+        let contents = fs::read_to_string(&config).unwrap();
+        assert!(contents.starts_with("import:\n  - ~/fonts.yml\n  - ~/keys.yml\n  - "));
+        assert!(contents.ends_with("\n\n# window stuff\nwindow:\n  opacity: 0.9\n"));
+        let value: serde_yaml::Value = serde_yaml::from_str(&contents).unwrap();
+        assert_eq!(
+            value["import"][2].as_str(),
+            Some(import_file.to_str().unwrap())
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn present_import_is_left_alone() {
+        let dir = temp_dir("import-present");
+        let import_file = dir.join("theme.toml");
+        for (file_name, contents) in [
+            (
+                "alacritty.toml",
+                "[general]\nimport = [\"theme.toml\"]\n".to_string(),
+            ),
+            (
+                "alacritty.yml",
+                format!("import:\n  - {}\n", import_file.display()),
+            ),
+        ] {
+            let config = dir.join(file_name);
+            fs::write(&config, &contents).unwrap();
+
+            let applied = ensure_import(&config, &import_file).unwrap();
+
+            assert!(!applied.import_added);
+            assert!(applied.backup.is_none());
+            assert_eq!(fs::read_to_string(&config).unwrap(), contents);
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,314 @@
+//! How readable the colors of a theme are, measured with the WCAG 2 contrast ratio.
+
+use serde::Serialize;
+
+use std::path::{Path, PathBuf};
+
+use crate::color::{CellRgb, Oklch, Rgb};
+use crate::config::{read_theme_colors, set_colors};
+use crate::error::{read_file, write_file, Error};
+use crate::theme::{load_theme, YmlColors};
+
+/// The contrast ratio of WCAG AA for normal text, which `fix-contrast` aims for and `derive`
+/// warns below by default.
+pub const DEFAULT_MIN_RATIO: f64 = 4.5;
+
+/// The WCAG 2 level a contrast ratio meets.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Grade {
+    /// Below 3:1.
+    Fail,
+    /// At least 3:1, enough for large or bold text.
+    AaLarge,
+    /// At least 4.5:1.
+    Aa,
+    /// At least 7:1.
+    Aaa,
+}
+
+impl Grade {
+    pub fn from_ratio(ratio: f64) -> Self {
+        if ratio >= 7.0 {
+            Grade::Aaa
+        } else if ratio >= 4.5 {
+            Grade::Aa
+        } else if ratio >= 3.0 {
+            Grade::AaLarge
+        } else {
+            Grade::Fail
+        }
+    }
+}
+
+impl std::fmt::Display for Grade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Grade::Fail => write!(f, "fail"),
+            Grade::AaLarge => write!(f, "AA large"),
+            Grade::Aa => write!(f, "AA"),
+            Grade::Aaa => write!(f, "AAA"),
+        }
+    }
+}
+
+/// The contrast of one pair of colors of a theme.
+#[derive(Clone, Debug, Serialize)]
+pub struct ContrastCheck {
+    /// What is checked, `foreground`, `normal.red`, `cursor` or `selection`.
+    pub name: String,
+    pub foreground: Rgb,
+    pub background: Rgb,
+    pub ratio: f64,
+    pub grade: Grade,
+    /// `false` for the ansi color that is meant to blend into the background, black on dark
+    /// themes and white on light ones. It does not count towards the grade of the theme.
+    pub required: bool,
+}
+
+impl ContrastCheck {
+    fn new(name: impl Into<String>, foreground: Rgb, background: Rgb, required: bool) -> Self {
+        let ratio = foreground.contrast_ratio(background);
+
+        Self {
+            name: name.into(),
+            foreground,
+            background,
+            ratio,
+            grade: Grade::from_ratio(ratio),
+            required,
+        }
+    }
+}
+
+/// Every contrast check of a theme.
+#[derive(Clone, Debug, Serialize)]
+pub struct ContrastReport {
+    pub checks: Vec<ContrastCheck>,
+}
+
+// the ansi color that is meant to be hard to see, black on dark and white on light backgrounds
+fn blends_in(prefix: &str, name: &str, background: Rgb) -> bool {
+    // light text is readable on a dark background
+    let dark = background.contrasting_label() == Rgb::WHITE;
+
+    prefix == "normal" && name == if dark { "black" } else { "white" }
+}
+
+// the color of a cell color, the cursor and selection colors can refer to the cell they are drawn
+// on which has the primary colors
+fn resolve(color: CellRgb, foreground: Rgb, background: Rgb) -> Rgb {
+    match color {
+        CellRgb::CellForeground => foreground,
+        CellRgb::CellBackground => background,
+        CellRgb::Rgb(rgb) => rgb,
+    }
+}
+
+impl ContrastReport {
+    /// Check the foreground, the 16 ansi colors and the cursor against the background, and the
+    /// selected text against the selection. Missing cursor and selection colors get alacritty's
+    /// defaults, which are the primary colors swapped.
+    pub fn new(colors: &YmlColors) -> Self {
+        let foreground = colors.primary.foreground;
+        let background = colors.primary.background;
+
+        let mut checks = vec![ContrastCheck::new(
+            "foreground",
+            foreground,
+            background,
+            true,
+        )];
+
+        for (prefix, entries) in [
+            ("normal", colors.normal.entries()),
+            ("bright", colors.bright.entries()),
+        ] {
+            for (name, color) in entries {
+                checks.push(ContrastCheck::new(
+                    format!("{prefix}.{name}"),
+                    color,
+                    background,
+                    !blends_in(prefix, name, background),
+                ));
+            }
+        }
+
+        let cursor = colors
+            .cursor
+            .as_ref()
+            .and_then(|cursor| cursor.cursor)
+            .unwrap_or(CellRgb::CellForeground);
+        checks.push(ContrastCheck::new(
+            "cursor",
+            resolve(cursor, foreground, background),
+            background,
+            true,
+        ));
+
+        let selection = colors.selection.clone().unwrap_or_default();
+        let text = selection.text.unwrap_or(CellRgb::CellBackground);
+        let selection = selection.background.unwrap_or(CellRgb::CellForeground);
+        checks.push(ContrastCheck::new(
+            "selection",
+            resolve(text, foreground, background),
+            resolve(selection, foreground, background),
+            true,
+        ));
+
+        Self { checks }
+    }
+
+    /// The required check with the lowest ratio.
+    pub fn lowest(&self) -> Option<&ContrastCheck> {
+        self.checks
+            .iter()
+            .filter(|check| check.required)
+            .min_by(|a, b| a.ratio.total_cmp(&b.ratio))
+    }
+
+    /// The grade of the theme, the grade its least readable required check meets.
+    pub fn grade(&self) -> Grade {
+        self.lowest().map_or(Grade::Aaa, |check| check.grade)
+    }
+
+    /// The required checks below `min_ratio`.
+    pub fn below(&self, min_ratio: f64) -> impl Iterator<Item = &ContrastCheck> {
+        self.checks
+            .iter()
+            .filter(move |check| check.required && check.ratio < min_ratio)
+    }
+}
+
+/// Make `color` lighter or darker in OKLCH until it has a contrast ratio of at least `target`
+/// against `background`, away from the background so the change is as small as possible. The
+/// hue and chroma are kept where sRGB allows it. When `target` can't be reached the most readable
+/// color in that direction is returned.
+pub fn nudge_lightness(color: Rgb, background: Rgb, target: f64) -> Rgb {
+    if color.contrast_ratio(background) >= target {
+        return color;
+    }
+
+    let start = Oklch::from(color);
+    let end = if background.contrasting_label() == Rgb::WHITE {
+        1.0
+    } else {
+        0.0
+    };
+    let at = |t: f64| {
+        Oklch {
+            l: start.l + (end - start.l) * t,
+            ..start
+        }
+        .to_rgb()
+    };
+
+    if at(1.0).contrast_ratio(background) < target {
+        return at(1.0);
+    }
+
+    // the contrast grows with the distance to the start, find the smallest distance that is
+    // enough
+    let (mut low, mut high) = (0.0, 1.0);
+
+    for _ in 0..24 {
+        let t = (low + high) / 2.0;
+
+        if at(t).contrast_ratio(background) >= target {
+            high = t;
+        } else {
+            low = t;
+        }
+    }
+
+    at(high)
+}
+
+/// A palette color changed by [`fix_contrast`].
+#[derive(Clone, Debug)]
+pub struct FixedColor {
+    /// The key of the color, like `normal.blue`.
+    pub name: String,
+    pub from: Rgb,
+    pub to: Rgb,
+    /// The contrast ratio of the new color, below the target when it could not be reached.
+    pub ratio: f64,
+}
+
+/// Nudge the normal and bright colors of `colors` below `target` against the background, see
+/// [`nudge_lightness`]. The color that is meant to blend into the background is left alone.
+pub fn fix_palette(colors: &mut YmlColors, target: f64) -> Vec<FixedColor> {
+    let background = colors.primary.background;
+    let mut fixed = Vec::new();
+
+    for (prefix, entries) in [
+        ("normal", colors.normal.entries_mut()),
+        ("bright", colors.bright.entries_mut()),
+    ] {
+        for (name, color) in entries {
+            if blends_in(prefix, name, background) || color.contrast_ratio(background) >= target {
+                continue;
+            }
+
+            let to = nudge_lightness(*color, background, target);
+
+            fixed.push(FixedColor {
+                name: format!("{prefix}.{name}"),
+                from: *color,
+                to,
+                ratio: to.contrast_ratio(background),
+            });
+            *color = to;
+        }
+    }
+
+    fixed
+}
+
+/// The result of [`fix_contrast`].
+#[derive(Debug)]
+pub struct ContrastFix {
+    /// The fixed theme, `<stem>-contrast.<ext>` next to the original. Nothing is written when no
+    /// color had to be fixed.
+    pub path: PathBuf,
+    pub fixed: Vec<FixedColor>,
+}
+
+/// Fix the palette of the theme at `theme_path` with [`fix_palette`] and write the result as a
+/// new theme next to it, with ` (contrast)` appended to its name. The rest of the file keeps its
+/// formatting and comments.
+pub fn fix_contrast(theme_path: &Path, target: f64) -> Result<ContrastFix, Error> {
+    let stem = theme_path.file_stem().unwrap_or_default().to_string_lossy();
+    let path = match theme_path.extension() {
+        Some(ext) => {
+            theme_path.with_file_name(format!("{stem}-contrast.{}", ext.to_string_lossy()))
+        }
+        None => theme_path.with_file_name(format!("{stem}-contrast")),
+    };
+
+    let mut theme = load_theme(theme_path)?;
+    let fixed = fix_palette(&mut theme.colors, target);
+
+    if fixed.is_empty() {
+        return Ok(ContrastFix { path, fixed });
+    }
+
+    if path.exists() {
+        return Err(Error::AlreadyExists(path));
+    }
+
+    let mut colors = read_theme_colors(theme_path)?;
+
+    for color in &fixed {
+        let (prefix, name) = color.name.split_once('.').unwrap();
+        colors[prefix][name] = color.to.to_string().into();
+    }
+    if let Some(name) = &theme.colors.name {
+        colors["name"] = format!("{name} (contrast)").into();
+    }
+
+    let contents = set_colors(theme_path, &read_file(theme_path)?, &colors)?.contents;
+    write_file(&path, &contents)?;
+
+    Ok(ContrastFix { path, fixed })
+}
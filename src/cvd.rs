@@ -0,0 +1,127 @@
+//! Simulating color vision deficiencies, to find the colors of a theme that people with color
+//! blindness can't tell apart.
+
+use crate::color::{encode, linear, Oklch, Rgb};
+use crate::theme::YmlColors;
+
+/// Colors closer than this in OKLab, see [`Oklch::distance`], are hard to tell apart as text.
+pub const MIN_DISTANCE: f64 = 0.06;
+
+/// The ansi colors that carry meaning, black and white are told apart by lightness alone.
+const CHROMATIC: [&str; 6] = ["red", "green", "yellow", "blue", "magenta", "cyan"];
+
+/// A color vision deficiency, each one is the lack of one kind of cone.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Deficiency {
+    /// No red cones.
+    Protanopia,
+    /// No green cones, the most common one.
+    Deuteranopia,
+    /// No blue cones.
+    Tritanopia,
+}
+
+impl Deficiency {
+    pub const ALL: [Deficiency; 3] = [
+        Deficiency::Protanopia,
+        Deficiency::Deuteranopia,
+        Deficiency::Tritanopia,
+    ];
+
+    // the full severity matrices of Machado, Oliveira and Fernandes (2009), on linear sRGB
+    fn matrix(self) -> [[f64; 3]; 3] {
+        match self {
+            Deficiency::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            Deficiency::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            Deficiency::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
+
+    /// How `color` looks to someone with this deficiency.
+    pub fn simulate(self, color: Rgb) -> Rgb {
+        let rgb = [linear(color.r), linear(color.g), linear(color.b)];
+        let [r, g, b] = self
+            .matrix()
+            .map(|row| row.iter().zip(rgb).map(|(m, c)| m * c).sum());
+
+        Rgb {
+            r: encode(r),
+            g: encode(g),
+            b: encode(b),
+        }
+    }
+}
+
+impl std::fmt::Display for Deficiency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Deficiency::Protanopia => write!(f, "protanopia"),
+            Deficiency::Deuteranopia => write!(f, "deuteranopia"),
+            Deficiency::Tritanopia => write!(f, "tritanopia"),
+        }
+    }
+}
+
+/// Two ansi colors that look different, but not to someone with `deficiency`.
+#[derive(Clone, Debug)]
+pub struct ConfusedPair {
+    pub deficiency: Deficiency,
+    /// The keys of the colors, like `normal.red`.
+    pub first: String,
+    pub second: String,
+    /// The distance of the simulated colors.
+    pub distance: f64,
+}
+
+/// The pairs of normal and bright colors that are told apart with normal vision, but are closer
+/// than [`MIN_DISTANCE`] with one of the deficiencies. Only colors of the same palette are
+/// compared, red and bright red are meant to look alike.
+pub fn confused_pairs(colors: &YmlColors) -> Vec<ConfusedPair> {
+    let mut pairs = Vec::new();
+
+    for (prefix, entries) in [
+        ("normal", colors.normal.entries()),
+        ("bright", colors.bright.entries()),
+    ] {
+        let entries: Vec<(&str, Rgb)> = entries
+            .into_iter()
+            .filter(|(name, _)| CHROMATIC.contains(name))
+            .collect();
+
+        for (i, &(first, first_color)) in entries.iter().enumerate() {
+            for &(second, second_color) in &entries[i + 1..] {
+                if Oklch::from(first_color).distance(second_color.into()) < MIN_DISTANCE {
+                    continue;
+                }
+
+                for deficiency in Deficiency::ALL {
+                    let distance = Oklch::from(deficiency.simulate(first_color))
+                        .distance(deficiency.simulate(second_color).into());
+
+                    if distance < MIN_DISTANCE {
+                        pairs.push(ConfusedPair {
+                            deficiency,
+                            first: format!("{prefix}.{first}"),
+                            second: format!("{prefix}.{second}"),
+                            distance,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    pairs
+}
@@ -0,0 +1,217 @@
+//! New themes made from existing ones.
+
+use std::path::{Path, PathBuf};
+
+use crate::color::{Oklch, Rgb};
+use crate::config::{read_theme_colors, set_colors, ConfigFormat};
+use crate::contrast::ContrastReport;
+use crate::error::{read_file, write_file, Error};
+use crate::theme::{load_theme, YmlColor};
+
+/// The color with its OKLCH lightness mirrored around `center`, a lightness between 0 and 1, so
+/// colors darker than `center` become lighter by as much and the other way around. The hue is
+/// kept, the chroma too where sRGB allows it.
+pub fn invert_lightness(color: Rgb, center: f64) -> Rgb {
+    let color = Oklch::from(color);
+
+    Oklch {
+        l: 2.0 * center - color.l,
+        ..color
+    }
+    .to_rgb()
+}
+
+// every color in a `colors` section, `name` and `author` are not colors and are left alone
+fn invert_colors(value: &mut serde_yaml::Value, center: f64) {
+    match value {
+        serde_yaml::Value::String(color) => {
+            if let Ok(rgb) = color.parse::<Rgb>() {
+                *color = invert_lightness(rgb, center).to_string();
+            }
+        }
+        serde_yaml::Value::Sequence(sequence) => sequence
+            .iter_mut()
+            .for_each(|value| invert_colors(value, center)),
+        serde_yaml::Value::Mapping(mapping) => mapping
+            .iter_mut()
+            .filter(|(key, _)| !matches!(key.as_str(), Some("name" | "author")))
+            .for_each(|(_, value)| invert_colors(value, center)),
+        _ => {}
+    }
+}
+
+// `dark` and `light` swapped at the end of `s`, keeping the case of the first letter, or
+// `suffix` appended
+fn variant_of(s: &str, variant: &str, suffix: &str) -> String {
+    let other = if variant == "light" { "dark" } else { "light" };
+
+    for (from, to) in [
+        (other.to_string(), variant.to_string()),
+        (capitalize(other), capitalize(variant)),
+    ] {
+        if let Some(base) = s.strip_suffix(&from) {
+            return format!("{base}{to}");
+        }
+    }
+
+    format!("{s}{suffix}")
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// The result of [`derive_inverted`].
+#[derive(Debug)]
+pub struct Derived {
+    /// The new theme, next to the original.
+    pub path: PathBuf,
+    /// The contrast of the new theme, mirroring the lightness does not keep the contrast ratios.
+    pub report: ContrastReport,
+}
+
+/// Write the light counterpart of a dark theme, or the dark one of a light theme, next to it
+/// with [`invert_lightness`] applied to every color. The lightness is mirrored halfway between the
+/// background and the foreground, so the two swap and the other colors stay as far from the
+/// background as they were. Black and white swap too, in the normal, bright and dim colors, to
+/// keep their names. The file
+/// and theme names get `light` or `dark`, replacing the opposite one at their end. The rest of the
+/// file keeps its formatting and comments.
+pub fn derive_inverted(theme_path: &Path) -> Result<Derived, Error> {
+    let original = load_theme(theme_path)?.colors;
+    let center = (Oklch::from(original.primary.background).l
+        + Oklch::from(original.primary.foreground).l)
+        / 2.0;
+    // light text is readable on a dark background
+    let background = invert_lightness(original.primary.background, center);
+    let variant = if background.contrasting_label() == Rgb::WHITE {
+        "dark"
+    } else {
+        "light"
+    };
+
+    let mut colors = read_theme_colors(theme_path)?;
+    invert_colors(&mut colors, center);
+    // black is the dark color and white the light one, which of them blends into the background
+    // changes with the background
+    for group in ["normal", "bright", "dim"] {
+        let Some(ansi) = colors.get_mut(group).and_then(|ansi| ansi.as_mapping_mut()) else {
+            continue;
+        };
+
+        if let (Some(black), Some(white)) = (ansi.get("black"), ansi.get("white")) {
+            let (black, white) = (black.clone(), white.clone());
+
+            ansi.insert("black".into(), white);
+            ansi.insert("white".into(), black);
+        }
+    }
+
+    let stem = theme_path.file_stem().unwrap_or_default().to_string_lossy();
+    let file_name = variant_of(&stem, variant, &format!("-{variant}"));
+    let path = match theme_path.extension() {
+        Some(ext) => theme_path.with_file_name(format!("{file_name}.{}", ext.to_string_lossy())),
+        None => theme_path.with_file_name(file_name),
+    };
+
+    if path.exists() {
+        return Err(Error::AlreadyExists(path));
+    }
+
+    if let Some(name) = &original.name {
+        colors["name"] = variant_of(name, variant, &format!(" {}", capitalize(variant))).into();
+    }
+
+    let contents = set_colors(theme_path, &read_file(theme_path)?, &colors)?.contents;
+    // parsed again so the report sees the file as it is written
+    let theme: YmlColor = ConfigFormat::from_path(&path).parse(&path, &contents)?;
+    write_file(&path, &contents)?;
+
+    Ok(Derived {
+        path,
+        report: ContrastReport::new(&theme.colors),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variant_names() {
+        assert_eq!(variant_of("dracula", "light", "-light"), "dracula-light");
+        assert_eq!(
+            variant_of("gruvbox-dark", "light", "-light"),
+            "gruvbox-light"
+        );
+        assert_eq!(
+            variant_of("Gruvbox Dark", "light", " Light"),
+            "Gruvbox Light"
+        );
+        assert_eq!(
+            variant_of("solarized_light", "dark", "-dark"),
+            "solarized_dark"
+        );
+        // only at the end
+        assert_eq!(variant_of("darkside", "light", "-light"), "darkside-light");
+        assert_eq!(capitalize("light"), "Light");
+        assert_eq!(capitalize(""), "");
+    }
+
+    #[test]
+    fn lightness_is_mirrored() {
+        assert_eq!(invert_lightness(Rgb::BLACK, 0.5), Rgb::WHITE);
+        assert_eq!(invert_lightness(Rgb::WHITE, 0.5), Rgb::BLACK);
+
+        let gray = Rgb {
+            r: 0x77,
+            g: 0x77,
+            b: 0x77,
+        };
+        let center = Oklch::from(gray).l;
+        assert_eq!(invert_lightness(gray, center), gray);
+
+        // the hue is kept
+        let red = Rgb {
+            r: 0xcc,
+            g: 0x24,
+            b: 0x1d,
+        };
+        let inverted = Oklch::from(invert_lightness(red, 0.6));
+        assert!((inverted.h - Oklch::from(red).h).abs() < 2.0);
+        assert!(inverted.l > Oklch::from(red).l);
+    }
+
+    #[test]
+    fn black_and_white_swap_in_every_group() {
+        let dir = std::env::temp_dir().join(format!("ats-derive-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let theme = dir.join("night.toml");
+        let ansi = "black = \"#1d1f21\"\nred = \"#cc6666\"\ngreen = \"#b5bd68\"\nyellow = \"#f0c674\"\nblue = \"#81a2be\"\nmagenta = \"#b294bb\"\ncyan = \"#8abeb7\"\nwhite = \"#c5c8c6\"\n";
+        std::fs::write(
+            &theme,
+            format!(
+                "[colors]\nname = \"Night\"\n\n[colors.primary]\nbackground = \"#1d1f21\"\nforeground = \"#c5c8c6\"\n\n[colors.normal]\n{ansi}\n[colors.bright]\n{ansi}\n[colors.dim]\n{ansi}"
+            ),
+        )
+        .unwrap();
+
+        let derived = derive_inverted(&theme).unwrap();
+        let colors = load_theme(&derived.path).unwrap().colors;
+
+        assert_eq!(derived.path, dir.join("night-light.toml"));
+        assert_eq!(colors.name.as_deref(), Some("Night Light"));
+        assert_eq!(colors.primary.background, colors.normal.white);
+        for group in [&colors.normal, &colors.bright, colors.dim.as_ref().unwrap()] {
+            let (black, white) = (Oklch::from(group.black).l, Oklch::from(group.white).l);
+            assert!(black < white, "{black} {white}");
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
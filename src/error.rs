@@ -0,0 +1,112 @@
+//! The error type shared by the whole crate.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Everything that can go wrong while finding, reading or writing configs and themes. The
+/// variants carry the path and cause so the message can be shown to the user as is.
+#[derive(Debug)]
+pub enum Error {
+    /// A required environment variable is not set.
+    MissingEnvVar {
+        name: &'static str,
+        source: env::VarError,
+    },
+    /// An environment variable has a value that can't be used.
+    InvalidEnvVar {
+        name: &'static str,
+        value: String,
+    },
+    /// No alacritty config file exists in any of the searched locations, `tried` lists them in
+    /// the order they were checked.
+    ConfigNotFound {
+        tried: Vec<PathBuf>,
+    },
+    ThemesDirNotFound(PathBuf),
+    ThemeNotFound(String),
+    /// A file that would be created already exists.
+    AlreadyExists(PathBuf),
+    /// Reading, writing or copying a file failed, `action` says which.
+    Io {
+        action: &'static str,
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// A config or theme file is not valid yaml/toml or does not match the theme model.
+    Parse {
+        path: PathBuf,
+        message: String,
+    },
+    Serialize {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::MissingEnvVar { name, source } => {
+                write!(f, "failed to get {name} env var: {source}")
+            }
+            Error::InvalidEnvVar { name, value } => {
+                write!(f, "invalid {name} env var: '{value}'")
+            }
+            Error::ConfigNotFound { tried } => {
+                write!(f, "alacritty config file not found, tried:")?;
+                for path in tried {
+                    write!(f, "\n  {}", path.display())?;
+                }
+                Ok(())
+            }
+            Error::ThemesDirNotFound(path) => {
+                write!(f, "themes dir '{}' not found", path.display())
+            }
+            Error::ThemeNotFound(name) => write!(f, "theme '{name}' not found"),
+            Error::AlreadyExists(path) => write!(f, "'{}' already exists", path.display()),
+            Error::Io {
+                action,
+                path,
+                source,
+            } => write!(f, "failed to {action} '{}': {source}", path.display()),
+            Error::Parse { path, message } => {
+                write!(f, "failed to parse '{}': {message}", path.display())
+            }
+            Error::Serialize { path, message } => {
+                write!(f, "failed to serialize '{}': {message}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::MissingEnvVar { source, .. } => Some(source),
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn env_var(name: &'static str) -> Result<String, Error> {
+    env::var(name).map_err(|source| Error::MissingEnvVar { name, source })
+}
+
+pub(crate) fn read_file(path: &Path) -> Result<String, Error> {
+    fs::read_to_string(path).map_err(|source| Error::Io {
+        action: "read",
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+pub(crate) fn write_file(path: &Path, contents: &str) -> Result<(), Error> {
+    fs::write(path, contents).map_err(|source| Error::Io {
+        action: "write",
+        path: path.to_path_buf(),
+        source,
+    })
+}
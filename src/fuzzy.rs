@@ -0,0 +1,58 @@
+// Fuzzy matching for the theme search, the characters of the pattern have to appear in the text
+// in order. Matches on word starts and runs of consecutive characters score higher.
+
+const MATCH_SCORE: i64 = 16;
+const CONSECUTIVE_BONUS: i64 = 24;
+const WORD_START_BONUS: i64 = 16;
+const GAP_PENALTY: i64 = 1;
+
+// a character that starts a word, after a separator or a lowercase to uppercase change
+fn is_word_start(previous: Option<char>, current: char) -> bool {
+    match previous {
+        None => true,
+        Some(previous) => {
+            !previous.is_alphanumeric() || (previous.is_lowercase() && current.is_uppercase())
+        }
+    }
+}
+
+// the positions of the matched characters of `text`, and the score of the match. The pattern is
+// matched case insensitively, an empty pattern matches everything.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+    let pattern: Vec<char> = pattern.chars().flat_map(char::to_lowercase).collect();
+    let chars: Vec<char> = text.chars().collect();
+
+    let mut positions: Vec<usize> = Vec::with_capacity(pattern.len());
+    let mut score = 0;
+    let mut pattern_index = 0;
+
+    for (i, &c) in chars.iter().enumerate() {
+        if pattern_index == pattern.len() {
+            break;
+        }
+
+        if !c.to_lowercase().eq(std::iter::once(pattern[pattern_index])) {
+            continue;
+        }
+
+        score += MATCH_SCORE;
+        if positions.last().is_some_and(|&last| last + 1 == i) {
+            score += CONSECUTIVE_BONUS;
+        } else if let Some(&last) = positions.last() {
+            score -= GAP_PENALTY * (i - last - 1) as i64;
+        }
+        if is_word_start(i.checked_sub(1).map(|j| chars[j]), c) {
+            score += WORD_START_BONUS;
+        }
+
+        positions.push(i);
+        pattern_index += 1;
+    }
+
+    if pattern_index < pattern.len() {
+        return None;
+    }
+
+    // prefer the shorter text when everything else is equal
+    Some((score - chars.len() as i64 / 8, positions))
+}
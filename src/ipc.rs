@@ -0,0 +1,302 @@
+//! Recoloring running alacritty windows through alacritty's IPC socket, the same way
+//! `alacritty msg config` does. The options only live as long as the window, the config file is
+//! not changed.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::color::Rgb;
+use crate::config::{read_theme_colors, yaml_to_toml};
+use crate::error::{env_var, Error};
+
+/// Keys of the theme files that are not alacritty options.
+const METADATA_KEYS: [&str; 2] = ["name", "author"];
+
+// alacritty's `SocketMessage::Config`
+#[derive(Serialize)]
+enum SocketMessage {
+    Config(IpcConfig),
+}
+
+#[derive(Serialize)]
+struct IpcConfig {
+    options: Vec<String>,
+    window_id: Option<i128>,
+    reset: bool,
+}
+
+/// The socket of a running alacritty and the window to recolor.
+#[derive(Clone, Debug)]
+pub struct Ipc {
+    pub socket: PathBuf,
+    /// `None` recolors every window of the alacritty instance.
+    pub window_id: Option<i128>,
+}
+
+impl Ipc {
+    /// The socket alacritty set in `$ALACRITTY_SOCKET` for the shells it runs. Only the window of
+    /// `$ALACRITTY_WINDOW_ID` is recolored, unless `all_windows` is set or the variable is
+    /// missing.
+    pub fn from_env(all_windows: bool) -> Result<Self, Error> {
+        let socket = PathBuf::from(env_var("ALACRITTY_SOCKET")?);
+
+        let window_id = match env::var("ALACRITTY_WINDOW_ID") {
+            Ok(id) if !all_windows => Some(id.parse().map_err(|_| Error::InvalidEnvVar {
+                name: "ALACRITTY_WINDOW_ID",
+                value: id,
+            })?),
+            _ => None,
+        };
+
+        Ok(Self { socket, window_id })
+    }
+
+    /// The window the switcher runs in, `$ALACRITTY_WINDOW_ID` has to be set so no other window
+    /// is recolored by accident.
+    pub fn current_window() -> Result<Self, Error> {
+        let socket = PathBuf::from(env_var("ALACRITTY_SOCKET")?);
+        let id = env_var("ALACRITTY_WINDOW_ID")?;
+
+        let window_id = id.parse().map_err(|_| Error::InvalidEnvVar {
+            name: "ALACRITTY_WINDOW_ID",
+            value: id,
+        })?;
+
+        Ok(Self {
+            socket,
+            window_id: Some(window_id),
+        })
+    }
+
+    /// Override the colors of the window with the theme at `theme_path`. Options set by earlier
+    /// calls are reset first, so no color of the previous theme is left over.
+    pub fn apply_theme(&self, theme_path: &Path) -> Result<(), Error> {
+        let options = color_options(theme_path)?;
+
+        self.reset()?;
+        self.send(options, false)
+    }
+
+    /// Drop every option set over IPC, the window goes back to the colors of its config.
+    pub fn reset(&self) -> Result<(), Error> {
+        self.send(Vec::new(), true)
+    }
+
+    // one message per connection, alacritty reads a single line. A message either resets the
+    // options set earlier or adds `options`, alacritty ignores the options of a reset
+    #[cfg(unix)]
+    fn send(&self, options: Vec<String>, reset: bool) -> Result<(), Error> {
+        use std::io::Write;
+        use std::os::unix::net::UnixStream;
+
+        let message = SocketMessage::Config(IpcConfig {
+            options,
+            window_id: self.window_id,
+            reset,
+        });
+        let message = serde_json::to_string(&message).map_err(|e| Error::Serialize {
+            path: self.socket.clone(),
+            message: e.to_string(),
+        })?;
+
+        let ipc_error = |source| Error::Io {
+            action: "send to",
+            path: self.socket.clone(),
+            source,
+        };
+
+        let mut stream = UnixStream::connect(&self.socket).map_err(ipc_error)?;
+        stream
+            .write_all(format!("{message}\n").as_bytes())
+            .map_err(ipc_error)?;
+        stream.flush().map_err(ipc_error)?;
+
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn send(&self, _options: Vec<String>, _reset: bool) -> Result<(), Error> {
+        Err(Error::Io {
+            action: "send to",
+            path: self.socket.clone(),
+            source: std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "alacritty IPC is only available on unix",
+            ),
+        })
+    }
+}
+
+/// The colors of a theme as `colors.primary.background="#282828"` options.
+pub fn color_options(theme_path: &Path) -> Result<Vec<String>, Error> {
+    let colors = read_theme_colors(theme_path)?;
+
+    let Some(toml::Value::Table(mut colors)) = yaml_to_toml(&colors) else {
+        return Err(Error::Parse {
+            path: theme_path.to_path_buf(),
+            message: "`colors` is not a table".to_string(),
+        });
+    };
+
+    colors
+        .iter_mut()
+        .for_each(|(_, value)| normalize_colors(value));
+
+    let mut options = Vec::new();
+
+    for (key, value) in &colors {
+        if !METADATA_KEYS.contains(&key.as_str()) {
+            push_options(&mut options, &format!("colors.{key}"), value);
+        }
+    }
+
+    Ok(options)
+}
+
+// one option per value, tables are flattened into dotted keys and arrays are written inline
+fn push_options(options: &mut Vec<String>, key: &str, value: &toml::Value) {
+    match value {
+        toml::Value::Table(table) => {
+            for (name, value) in table {
+                push_options(options, &format!("{key}.{name}"), value);
+            }
+        }
+        value => options.push(format!("{key}={value}")),
+    }
+}
+
+// write every color as `#rrggbb`, alacritty does not understand the short `#rgb` form
+fn normalize_colors(value: &mut toml::Value) {
+    match value {
+        toml::Value::String(color) => {
+            if let Ok(rgb) = color.parse::<Rgb>() {
+                *color = rgb.to_string();
+            }
+        }
+        toml::Value::Array(array) => array.iter_mut().for_each(normalize_colors),
+        toml::Value::Table(table) => table
+            .iter_mut()
+            .for_each(|(_, value)| normalize_colors(value)),
+        _ => {}
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    use std::fs;
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::net::UnixListener;
+
+    // a fresh dir for each test, tests run in parallel
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("ats-ipc-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // run `send` against a listening socket and return the messages it wrote, one per connection
+    fn received(
+        dir: &Path,
+        send: impl FnOnce(&Ipc) -> Result<(), Error>,
+    ) -> Vec<serde_json::Value> {
+        let socket = dir.join("socket");
+        let listener = UnixListener::bind(&socket).unwrap();
+        let ipc = Ipc {
+            socket,
+            window_id: Some(1),
+        };
+
+        send(&ipc).unwrap();
+        listener.set_nonblocking(true).unwrap();
+
+        // the connections are queued until they are accepted
+        let mut messages = Vec::new();
+        while let Ok((stream, _)) = listener.accept() {
+            stream.set_nonblocking(false).unwrap();
+            let mut line = String::new();
+            BufReader::new(stream).read_line(&mut line).unwrap();
+            messages.push(serde_json::from_str(&line).unwrap());
+        }
+
+        messages
+    }
+
+    const THEME: &str = r##"[colors]
+name = "Test"
+author = "Someone"
+
+[colors.primary]
+background = "#abc"
+foreground = "0xffffff"
+
+[colors.normal]
+red = "#cc0000"
+"##;
+
+    #[test]
+    fn apply_theme_sends_the_colors() {
+        let dir = temp_dir("apply");
+        let theme = dir.join("test.toml");
+        fs::write(&theme, THEME).unwrap();
+
+        let messages = received(&dir, |ipc| ipc.apply_theme(&theme));
+
+        assert_eq!(
+            messages,
+            [
+                serde_json::json!({
+                    "Config": { "options": [], "window_id": 1, "reset": true }
+                }),
+                serde_json::json!({
+                    "Config": {
+                        "options": [
+                            "colors.primary.background=\"#aabbcc\"",
+                            "colors.primary.foreground=\"#ffffff\"",
+                            "colors.normal.red=\"#cc0000\"",
+                        ],
+                        "window_id": 1,
+                        "reset": false,
+                    }
+                }),
+            ]
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reset_sends_no_options() {
+        let dir = temp_dir("reset");
+
+        let messages = received(&dir, Ipc::reset);
+
+        assert_eq!(
+            messages,
+            [serde_json::json!({
+                "Config": { "options": [], "window_id": 1, "reset": true }
+            })]
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn color_options_skip_metadata_and_expand_short_colors() {
+        let dir = temp_dir("options");
+        let theme = dir.join("test.yml");
+        fs::write(
+            &theme,
+            "colors:\n  name: Test\n  author: Someone\n  primary:\n    background: '#abc'\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            color_options(&theme).unwrap(),
+            ["colors.primary.background=\"#aabbcc\""]
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,30 @@
+//! Switch the colors of alacritty between themes.
+//!
+//! The binary is a thin layer over this library, which finds the alacritty config and the themes
+//! dir, parses and validates themes, and writes them to the config.
+//!
+//! ```no_run
+//! use alacritty_theme_switcher::config::{apply_theme, find_alacritty_config_file, ApplyMode};
+//! use alacritty_theme_switcher::store::ThemeStore;
+//!
+//! let config = find_alacritty_config_file()?;
+//! let theme = ThemeStore::open_default()?.find("dracula")?;
+//!
+//! apply_theme(&config, &theme, ApplyMode::Rewrite)?;
+//! # Ok::<(), alacritty_theme_switcher::Error>(())
+//! ```
+
+pub mod color;
+pub mod config;
+pub mod contrast;
+pub mod cvd;
+pub mod derive;
+pub mod error;
+pub mod ipc;
+pub mod migrate;
+pub mod settings;
+pub mod store;
+pub mod theme;
+pub mod validate;
+
+pub use error::Error;
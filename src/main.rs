@@ -1,804 +1,596 @@
-// TODO: Refactor this code!
-// TODO: add fuzzy finding search
-
-use serde::{Deserialize, Serialize};
-use std::{io, io::stdout};
-
-use color_eyre::config::HookBuilder;
-use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind},
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-    ExecutableCommand,
-};
-use ratatui::{prelude::*, style::palette::tailwind, widgets::*};
-
-const TODO_HEADER_BG: Color = tailwind::BLUE.c950;
-const NORMAL_ROW_COLOR: Color = tailwind::SLATE.c950;
-const SELECTED_STYLE_FG: Color = tailwind::BLUE.c300;
-const TEXT_COLOR: Color = tailwind::SLATE.c200;
-
-use std::env;
-use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::exit;
 
-use clap::Parser;
+use alacritty_theme_switcher::config::{
+    apply_theme, backup_cfg_file, get_managed_theme_file, Applied, ApplyMode,
+};
+use alacritty_theme_switcher::contrast::{
+    fix_contrast, ContrastCheck, ContrastReport, DEFAULT_MIN_RATIO,
+};
+use alacritty_theme_switcher::cvd::confused_pairs;
+use alacritty_theme_switcher::derive::derive_inverted;
+use alacritty_theme_switcher::ipc::Ipc;
+use alacritty_theme_switcher::migrate::{is_yaml, migrate_backup_path, migrate_file, resolve_path};
+use alacritty_theme_switcher::settings::Settings;
+use alacritty_theme_switcher::store::ThemeStore;
+use alacritty_theme_switcher::theme::load_theme;
+use alacritty_theme_switcher::validate::validate_themes;
+use alacritty_theme_switcher::Error;
+use clap::{Parser, Subcommand};
+
+mod color_mode;
+mod fuzzy;
+mod sample;
+mod terminfo;
+mod tui;
+
+use color_mode::ColorMode;
+use tui::{init_error_hooks, init_terminal, restore_terminal, App};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     theme_name: Option<String>,
 
     #[arg(short, long, help = "Print current theme name")]
     print_current_theme: bool,
-}
-
-#[derive(Serialize, Deserialize)]
-struct YmlPrimary {
-    background: String,
-    foreground: String,
-}
 
-impl Default for YmlPrimary {
-    fn default() -> Self {
-        Self {
-            background: "#000000".into(),
-            foreground: "#000000".into(),
-        }
+    #[arg(
+        long,
+        global = true,
+        help = "Apply themes through a managed file imported by the config instead of rewriting the config"
+    )]
+    import: bool,
+
+    #[arg(
+        long,
+        help = "Don't write the selected theme to the config while browsing"
+    )]
+    no_preview: bool,
+
+    #[arg(
+        long,
+        help = "Recolor alacritty through its IPC socket, the themes list still writes the config when a theme is applied"
+    )]
+    ipc: bool,
+
+    #[arg(
+        long,
+        requires = "ipc",
+        help = "With --ipc, recolor every window instead of only the current one"
+    )]
+    all_windows: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["ipc", "import"],
+        help = "Recolor only the alacritty window the switcher runs in over IPC, the config is never changed"
+    )]
+    window: bool,
+
+    #[arg(
+        long,
+        global = true,
+        env = "ALACRITTY_THEME_SWITCHER_CONFIG",
+        value_name = "FILE",
+        help = "Alacritty config to write themes to instead of the discovered one"
+    )]
+    config: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        env = "ALACRITTY_THEME_SWITCHER_THEMES_DIR",
+        value_name = "DIR",
+        help = "Dir to read themes from instead of $XDG_CONFIG_HOME/alacritty/themes"
+    )]
+    themes_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = ColorMode::Auto,
+        value_name = "MODE",
+        help = "Colors the terminal can show, detected from $COLORTERM and the terminfo entry of $TERM by default"
+    )]
+    color_mode: ColorMode,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Convert the yaml config and every yaml theme to toml")]
+    Migrate,
+
+    #[command(about = "Check every theme in the themes dir for errors")]
+    Validate,
+
+    #[command(about = "Report the WCAG contrast ratios of a theme, or of every theme")]
+    Contrast {
+        theme_name: Option<String>,
+
+        #[arg(long, help = "Print the report as json")]
+        json: bool,
+
+        #[arg(
+            long,
+            value_name = "RATIO",
+            help = "Exit with an error when a theme has a contrast ratio below RATIO, like 4.5"
+        )]
+        min_ratio: Option<f64>,
+    },
+
+    #[command(
+        about = "Find ansi colors of a theme, or of every theme, that look alike to colorblind people"
+    )]
+    Cvd { theme_name: Option<String> },
+
+    #[command(
+        about = "Write a copy of a theme with its ansi colors lightened or darkened to meet a contrast ratio"
+    )]
+    FixContrast {
+        theme_name: String,
+
+        #[arg(
+            long,
+            value_name = "RATIO",
+            default_value_t = DEFAULT_MIN_RATIO,
+            help = "Contrast ratio every color needs against the background"
+        )]
+        target: f64,
+    },
+
+    #[command(about = "Write a light variant of a dark theme, or a dark variant of a light one")]
+    Derive {
+        theme_name: String,
+
+        #[arg(
+            long,
+            required = true,
+            help = "Mirror the lightness of every color, keeping its hue"
+        )]
+        invert_lightness: bool,
+
+        #[arg(
+            long,
+            value_name = "RATIO",
+            default_value_t = DEFAULT_MIN_RATIO,
+            help = "Warn when a color of the new theme has a contrast ratio below RATIO"
+        )]
+        min_ratio: f64,
+    },
+}
+
+fn print_backup(file: &Path, backup: Option<PathBuf>) {
+    if let Some(backup) = backup {
+        println!("backup: {} -> {}", file.display(), backup.display());
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-struct YmlCursor {
-    text: String,
-    cursor: String,
-}
+// print what applying a theme changed besides the colors
+fn print_applied(file: &Path, applied: Applied) {
+    print_backup(file, applied.backup);
 
-impl Default for YmlCursor {
-    fn default() -> Self {
-        Self {
-            text: "#000000".into(),
-            cursor: "#000000".into(),
-        }
+    if applied.import_added {
+        println!(
+            "import: added {} to {}",
+            resolve_path(&get_managed_theme_file(file)).display(),
+            file.display()
+        );
     }
-}
-
-#[derive(Serialize, Deserialize)]
-struct YmlNormal {
-    black: String,
-    red: String,
-    green: String,
-    yellow: String,
-    blue: String,
-    magenta: String,
-    cyan: String,
-    white: String,
-}
-
-impl Default for YmlNormal {
-    fn default() -> Self {
-        Self {
-            black: "#000000".into(),
-            red: "#000000".into(),
-            green: "#000000".into(),
-            yellow: "#000000".into(),
-            blue: "#000000".into(),
-            magenta: "#000000".into(),
-            cyan: "#000000".into(),
-            white: "#000000".into(),
-        }
+    if applied.rewritten {
+        eprintln!(
+            "warning: {} was written again as a whole and lost its comments, its colors define anchors used elsewhere",
+            file.display()
+        );
     }
-}
-
-#[derive(Serialize, Deserialize)]
-struct YmlBright {
-    black: String,
-    red: String,
-    green: String,
-    yellow: String,
-    blue: String,
-    magenta: String,
-    cyan: String,
-    white: String,
-}
-
-impl Default for YmlBright {
-    fn default() -> Self {
-        Self {
-            black: "#000000".into(),
-            red: "#000000".into(),
-            green: "#000000".into(),
-            yellow: "#000000".into(),
-            blue: "#000000".into(),
-            magenta: "#000000".into(),
-            cyan: "#000000".into(),
-            white: "#000000".into(),
-        }
+    if applied.colors_overridden {
+        eprintln!(
+            "warning: the colors in {} override the imported theme",
+            file.display()
+        );
     }
 }
 
-#[derive(Default, Serialize, Deserialize)]
-struct YmlColors {
-    name: Option<String>,
-    author: Option<String>,
-    primary: YmlPrimary,
-    cursor: Option<YmlCursor>,
-    normal: YmlNormal,
-    bright: YmlBright,
-}
-
-#[derive(Default, Serialize, Deserialize)]
-struct YmlColor {
-    colors: YmlColors,
-}
-
-fn find_alacritty_config_file() -> String {
-    let file = String::new();
+// returns the number of files that failed to migrate
+fn migrate(settings: &Settings) -> Result<usize, Error> {
+    let alacritty_cfg = settings.config_file()?;
+    let mut failed = 0;
 
-    let mut home = match env::var("HOME") {
-        Ok(v) => PathBuf::from(v),
-        Err(e) => {
-            eprintln!("Failed to get HOME env var!: {e}");
-            exit(1);
-        }
+    // only the user's own themes, the other sources are usually not writable
+    let mut files: Vec<PathBuf> = match ThemeStore::new(settings.themes_dir()?).theme_files() {
+        Ok(themes) => themes.into_iter().filter(|path| is_yaml(path)).collect(),
+        Err(_) => Vec::new(),
     };
 
-    home.push("alacritty.yml");
-
-    if home.exists() {
-        return home.to_str().unwrap().to_string();
-    }
-
-    let mut xdg_config_home: PathBuf = match env::var("XDG_CONFIG_HOME") {
-        Ok(v) => PathBuf::from(v),
-        Err(e) => {
-            eprintln!("Failed to get XDG_CONFIG_HOME env var!: {e}");
-            exit(1);
+    // the config goes last, so its imports of the files migrated before it can be updated
+    if is_yaml(&alacritty_cfg) {
+        let managed = get_managed_theme_file(&alacritty_cfg);
+        if managed.is_file() {
+            files.push(managed);
         }
-    };
-
-    xdg_config_home.push("alacritty");
-    xdg_config_home.push("alacritty.yml");
 
-    if xdg_config_home.exists() {
-        return xdg_config_home.to_str().unwrap().to_string();
+        print_backup(&alacritty_cfg, backup_cfg_file(&alacritty_cfg)?);
+        files.push(alacritty_cfg);
+    } else {
+        println!("config: {} is already toml", alacritty_cfg.display());
     }
 
-    file
-}
-
-fn get_themes_dir() -> PathBuf {
-    let mut dir: PathBuf = match env::var("XDG_CONFIG_HOME") {
-        Ok(v) => PathBuf::from(v),
-        Err(e) => {
-            eprintln!("Failed to get XDG_CONFIG_HOME env var!: {e}");
-            exit(1);
-        }
-    };
-
-    dir.push("alacritty");
-    dir.push("themes");
-
-    dir
-}
-
-// backup the main config file before doing any changes
-// first check if a backup already exists if not backup the file
-fn backup_cfg_file(file: &String) {
-    let mut backup_file = PathBuf::from(file);
-
-    // TODO: Rewrite this code
-    // Change 'alacritty.yml' -> 'alacritty-backup.yml'
-    let new_name = format!(
-        "{}-backup.{}",
-        backup_file.as_path().file_stem().unwrap().to_str().unwrap(),
-        backup_file.as_path().extension().unwrap().to_str().unwrap()
-    );
-    backup_file.set_file_name(new_name);
-
-    if !backup_file.exists() {
-        // backup config file
-        match fs::copy(file, &backup_file) {
-            Ok(_) => {
-                println!("backup: {} -> {}", file, backup_file.to_str().unwrap());
+    let mut migrated: Vec<PathBuf> = Vec::new();
+
+    for file in files {
+        // resolved before the file is renamed
+        let resolved = resolve_path(&file);
+
+        match migrate_file(&file, &migrated) {
+            Ok(toml_path) => {
+                println!(
+                    "migrate: {} -> {}, kept as {}",
+                    file.display(),
+                    toml_path.display(),
+                    migrate_backup_path(&file).display()
+                );
+                migrated.push(resolved);
             }
             Err(e) => {
-                eprintln!("Failed to backup alacritty config file: {e}");
+                eprintln!("Failed to migrate '{}': {e}", file.display());
+                failed += 1;
             }
         }
     }
-}
-
-fn apply_theme(file_path: &String, theme_path: &str) {
-    let alacritty_cfg_contents = fs::read_to_string(file_path).expect("File not found");
-    let theme_file_contents = fs::read_to_string(theme_path).expect("File not found");
-
-    let mut color: serde_yaml::Value = serde_yaml::from_str(&alacritty_cfg_contents).unwrap();
-    let new_theme_color: serde_yaml::Value = serde_yaml::from_str(&theme_file_contents).unwrap();
-
-    color["colors"] = new_theme_color["colors"].clone();
-
-    let new_cfg_file = serde_yaml::to_string(&color).unwrap();
-
-    match fs::write(file_path, new_cfg_file) {
-        Ok(_) => {}
-        Err(e) => {
-            eprintln!("Failed to write to alacritty config file: {e}");
-        }
-    }
-}
 
-struct ListItem {
-    name: String,
-    path: String,
+    Ok(failed)
 }
 
-struct StatefulList {
-    state: ListState,
-    alacritty_cfg_file: String,
-    items: Vec<ListItem>,
-    last_selected: Option<usize>,
+// the themes of a missing source are left out instead of failing the whole command
+fn warn_unavailable_sources(store: &ThemeStore) {
+    for source in store.unavailable_sources() {
+        eprintln!(
+            "warning: the theme source `{}` ({}) doesn't exist, skipping it",
+            source.name,
+            source.dir.display()
+        );
+    }
 }
 
-struct App {
-    items: StatefulList,
-}
+// returns the number of errors found, only the themes the user maintains are checked, the system
+// themes are not theirs to fix
+fn validate(settings: &Settings) -> Result<usize, Error> {
+    let store = settings.own_theme_store()?;
+    warn_unavailable_sources(&store);
 
-fn get_themes() -> Vec<ListItem> {
-    let alacritty_config_file_path: String = find_alacritty_config_file();
-    let themes_dir: PathBuf = get_themes_dir();
-    let mut themes_list: Vec<ListItem> = Vec::new();
+    let report = validate_themes(&store)?;
 
-    if !themes_dir.exists() {
-        eprintln!("Themes dir not found!");
-        exit(1);
+    for diagnostic in &report.diagnostics {
+        eprintln!("{diagnostic}");
     }
 
-    if !Path::new(&alacritty_config_file_path).exists() {
-        eprintln!("Config file not found!");
-        exit(1);
-    }
+    println!(
+        "validate: checked {} themes, found {} errors",
+        report.themes,
+        report.diagnostics.len()
+    );
 
-    backup_cfg_file(&alacritty_config_file_path);
-
-    for entry in themes_dir.read_dir().unwrap() {
-        match entry {
-            Ok(file) => {
-                let item = ListItem {
-                    name: file
-                        .path()
-                        .as_path()
-                        .file_name()
-                        .unwrap()
-                        .to_str()
-                        .unwrap()
-                        .to_string(),
-                    path: file.path().as_path().to_str().unwrap().to_string(),
-                };
-                themes_list.push(item);
-            }
+    Ok(report.diagnostics.len())
+}
+
+// the theme called `theme_name`, or every theme named by its path in its source like
+// `alacritty-theme/themes/dracula`
+fn named_themes(
+    settings: &Settings,
+    theme_name: Option<&str>,
+) -> Result<Vec<(String, PathBuf)>, Error> {
+    let store = settings.theme_store()?;
+    warn_unavailable_sources(&store);
+
+    Ok(match theme_name {
+        Some(name) => vec![(name.to_string(), store.find(name)?)],
+        None => store
+            .themes()?
+            .into_iter()
+            .map(|entry| {
+                let name = entry.group.join(entry.stem()).display().to_string();
+                (name, entry.path)
+            })
+            .collect(),
+    })
+}
+
+// returns the number of themes that failed to load or are below `min_ratio`
+fn contrast(
+    settings: &Settings,
+    theme_name: Option<&str>,
+    json: bool,
+    min_ratio: Option<f64>,
+) -> Result<usize, Error> {
+    let themes = named_themes(settings, theme_name)?;
+
+    let mut failed = 0;
+    let mut reports = Vec::new();
+
+    for (name, path) in themes {
+        let report = match load_theme(&path) {
+            Ok(theme) => ContrastReport::new(&theme.colors),
             Err(e) => {
-                eprintln!("Failed to read_dir on file: {e}");
-                exit(1);
+                eprintln!("{e}");
+                failed += 1;
+                continue;
             }
-        }
-    }
-
-    themes_list
-}
+        };
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let cli = Cli::parse();
+        let below: Vec<&ContrastCheck> = min_ratio
+            .map(|min_ratio| report.below(min_ratio).collect())
+            .unwrap_or_default();
+        if !below.is_empty() {
+            failed += 1;
+        }
 
-    if cli.print_current_theme {
-        let alacritty_cfg_path = find_alacritty_config_file();
+        if json {
+            reports.push(serde_json::json!({
+                "name": name,
+                "path": path,
+                "grade": report.grade(),
+                "checks": report.checks,
+            }));
+            continue;
+        }
 
-        if !Path::new(&alacritty_cfg_path).exists() {
-            eprintln!("alacritty cfg: '{}' not found!", alacritty_cfg_path);
-            exit(1);
+        if theme_name.is_some() {
+            println!("{name}: {}", report.grade());
+            for check in &report.checks {
+                println!(
+                    "  {:<16} {} on {} {:>6.2}:1  {}{}",
+                    check.name,
+                    check.foreground,
+                    check.background,
+                    check.ratio,
+                    check.grade,
+                    if check.required {
+                        ""
+                    } else {
+                        " (not required)"
+                    }
+                );
+            }
+        } else {
+            let lowest = report.lowest().expect("the foreground is always checked");
+            println!(
+                "{name:<32} {:<8} lowest: {} {:.2}:1",
+                report.grade().to_string(),
+                lowest.name,
+                lowest.ratio
+            );
         }
 
-        let alacritty_cfg_str = fs::read_to_string(alacritty_cfg_path).unwrap();
-        let file: YmlColor = serde_yaml::from_str(&alacritty_cfg_str).unwrap();
+        for check in below {
+            eprintln!(
+                "{name}: {} {:.2}:1 is below {}:1",
+                check.name,
+                check.ratio,
+                min_ratio.unwrap()
+            );
+        }
+    }
 
+    if json {
         println!(
             "{}",
-            file.colors.name.unwrap_or("ERROR: name not found".into())
+            serde_json::to_string_pretty(&reports).expect("the reports are valid json")
         );
-
-        exit(0);
     }
 
-    if let Some(theme_name) = cli.theme_name {
-        let mut themes_dir = get_themes_dir();
-
-        if !theme_name.ends_with(".yml") {
-            themes_dir.push(format!("{}.yml", theme_name));
-        } else {
-            themes_dir.push(theme_name.clone());
-        }
+    Ok(failed)
+}
 
-        if !themes_dir.exists() {
-            eprintln!("Theme '{}' not found", theme_name);
-            exit(1);
-        }
+// returns the number of confused pairs
+fn cvd(settings: &Settings, theme_name: Option<&str>) -> Result<usize, Error> {
+    let themes = named_themes(settings, theme_name)?;
 
-        let alacritty_cfg = find_alacritty_config_file();
+    let mut found = 0;
 
-        apply_theme(&alacritty_cfg, themes_dir.as_path().to_str().unwrap());
+    for (name, path) in &themes {
+        let theme = match load_theme(path) {
+            Ok(theme) => theme,
+            Err(e) => {
+                eprintln!("{e}");
+                continue;
+            }
+        };
 
-        exit(0);
+        for pair in confused_pairs(&theme.colors) {
+            println!(
+                "{name}: {} and {} look alike with {} ({:.3})",
+                pair.first, pair.second, pair.deficiency, pair.distance
+            );
+            found += 1;
+        }
     }
 
-    init_error_hooks()?;
-    let terminal = init_terminal()?;
+    println!(
+        "cvd: checked {} themes, found {found} pairs that look alike",
+        themes.len()
+    );
 
-    // create app and run it
-    App::new().run(terminal)?;
+    Ok(found)
+}
 
-    restore_terminal()?;
+fn main() {
+    let cli = Cli::parse();
 
-    Ok(())
+    if let Err(e) = run(cli) {
+        eprintln!("Error: {e}");
+        exit(1);
+    }
 }
 
-fn init_error_hooks() -> color_eyre::Result<()> {
-    let (panic, error) = HookBuilder::default().into_hooks();
-    let panic = panic.into_panic_hook();
-    let error = error.into_eyre_hook();
-    color_eyre::eyre::set_hook(Box::new(move |e| {
-        let _ = restore_terminal();
-        error(e)
-    }))?;
-    std::panic::set_hook(Box::new(move |info| {
-        let _ = restore_terminal();
-        panic(info);
-    }));
-    Ok(())
-}
+fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let apply_mode = if cli.import {
+        ApplyMode::Import
+    } else {
+        ApplyMode::Rewrite
+    };
 
-fn init_terminal() -> color_eyre::Result<Terminal<impl Backend>> {
-    enable_raw_mode()?;
-    stdout().execute(EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout());
-    let terminal = Terminal::new(backend)?;
-    Ok(terminal)
-}
+    // the command line and env vars take precedence over the settings file
+    let settings = Settings::load()?.merge(Settings {
+        config: cli.config,
+        themes_dir: cli.themes_dir,
+        ..Settings::default()
+    });
 
-fn restore_terminal() -> color_eyre::Result<()> {
-    disable_raw_mode()?;
-    stdout().execute(LeaveAlternateScreen)?;
-    Ok(())
-}
+    match cli.command {
+        Some(Command::Migrate) => {
+            let failed = migrate(&settings)?;
 
-fn render_title(area: Rect, buf: &mut Buffer) {
-    Paragraph::new("Themes switcher")
-        .bold()
-        .centered()
-        .render(area, buf);
-}
+            exit(if failed > 0 { 1 } else { 0 });
+        }
+        Some(Command::Validate) => {
+            let errors = validate(&settings)?;
 
-fn render_footer(area: Rect, buf: &mut Buffer) {
-    Paragraph::new("\nUse ↓↑ to move, a to apply theme, g/G to go top/bottom.")
-        .centered()
-        .render(area, buf);
-}
+            exit(if errors > 0 { 1 } else { 0 });
+        }
+        Some(Command::Contrast {
+            theme_name,
+            json,
+            min_ratio,
+        }) => {
+            let failed = contrast(&settings, theme_name.as_deref(), json, min_ratio)?;
+
+            exit(if failed > 0 { 1 } else { 0 });
+        }
+        Some(Command::Cvd { theme_name }) => {
+            let pairs = cvd(&settings, theme_name.as_deref())?;
 
-impl StatefulList {
-    fn with_items(items: Vec<ListItem>) -> StatefulList {
-        StatefulList {
-            state: ListState::default(),
-            items,
-            alacritty_cfg_file: find_alacritty_config_file(),
-            last_selected: None,
+            exit(if pairs > 0 { 1 } else { 0 });
         }
-    }
+        Some(Command::FixContrast { theme_name, target }) => {
+            let theme_path = settings.theme_store()?.find(&theme_name)?;
+            let fix = fix_contrast(&theme_path, target)?;
 
-    fn next(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i >= self.items.len() - 1 {
-                    0
-                } else {
-                    i + 1
-                }
+            if fix.fixed.is_empty() {
+                println!("fix-contrast: every color of {theme_name} meets {target}:1");
+                exit(0);
             }
-            None => self.last_selected.unwrap_or(0),
-        };
-        self.state.select(Some(i));
-    }
 
-    fn previous(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.items.len() - 1
-                } else {
-                    i - 1
+            for color in &fix.fixed {
+                println!(
+                    "fix-contrast: {} {} -> {} ({:.2}:1)",
+                    color.name, color.from, color.to, color.ratio
+                );
+                if color.ratio < target {
+                    eprintln!(
+                        "warning: {} can't reach {target}:1 against the background",
+                        color.name
+                    );
                 }
             }
-            None => self.last_selected.unwrap_or(0),
-        };
-        self.state.select(Some(i));
-    }
-}
+            println!("fix-contrast: wrote {}", fix.path.display());
 
-pub fn decode_hex(s: &str) -> Result<Vec<u8>, std::num::ParseIntError> {
-    (0..s.len())
-        .step_by(2)
-        .map(|i| u8::from_str_radix(&s[i..i + 2], 16))
-        .collect()
-}
-
-fn hex_to_rgb(s: String) -> Color {
-    // #fff is the min
-    if s.is_empty() || s.len() < 3 {
-        return Color::Rgb(0, 0, 0);
-    }
-
-    let mut hex: String = s;
+            exit(0);
+        }
+        Some(Command::Derive {
+            theme_name,
+            min_ratio,
+            ..
+        }) => {
+            let theme_path = settings.theme_store()?.find(&theme_name)?;
+            let derived = derive_inverted(&theme_path)?;
+
+            println!("derive: wrote {}", derived.path.display());
+
+            let report = &derived.report;
+            let lowest = report.lowest().expect("the foreground is always checked");
+            println!(
+                "derive: contrast {} (lowest: {} {:.2}:1)",
+                report.grade(),
+                lowest.name,
+                lowest.ratio
+            );
+            if report.below(min_ratio).next().is_some() {
+                eprintln!(
+                    "warning: some colors are below {min_ratio}:1, see `contrast` and `fix-contrast`"
+                );
+            }
 
-    if hex.starts_with('#') {
-        hex = hex.replace('#', "");
-    } else if hex.starts_with("0x") {
-        hex = hex.replace("0x", "");
+            exit(0);
+        }
+        None => {}
     }
 
-    if hex.len() < 6 {
-        let c = hex.chars().next().unwrap();
-        let max = 6 - hex.len();
+    if cli.print_current_theme {
+        let mut alacritty_cfg_path = settings.config_file()?;
 
-        for _ in 0..max {
-            hex.push(c);
+        // in import mode the current theme lives in the managed file
+        if apply_mode == ApplyMode::Import {
+            alacritty_cfg_path = get_managed_theme_file(&alacritty_cfg_path);
         }
-    }
 
-    match decode_hex(&hex) {
-        Ok(v) => Color::Rgb(v[0], v[1], v[2]),
-        Err(_) => Color::Rgb(0, 0, 0),
-    }
-}
+        let file = load_theme(&alacritty_cfg_path)?;
 
-impl App {
-    fn new() -> Self {
-        Self {
-            items: StatefulList::with_items(get_themes()),
-        }
-    }
+        println!(
+            "{}",
+            file.colors.name.unwrap_or("ERROR: name not found".into())
+        );
 
-    fn go_top(&mut self) {
-        self.items.state.select(Some(0));
+        exit(0);
     }
 
-    fn go_bottom(&mut self) {
-        self.items.state.select(Some(self.items.items.len() - 1));
-    }
+    if let Some(theme_name) = cli.theme_name {
+        let theme_path = settings.theme_store()?.find(&theme_name)?;
 
-    fn apply_theme(&self) {
-        let theme_index = self.items.state.selected().unwrap();
+        if cli.window {
+            Ipc::current_window()?.apply_theme(&theme_path)?;
 
-        let theme = self.items.items.get(theme_index);
+            exit(0);
+        }
 
-        if let Some(theme) = theme {
-            apply_theme(&self.items.alacritty_cfg_file, &theme.path);
+        if cli.ipc {
+            match Ipc::from_env(cli.all_windows).and_then(|ipc| ipc.apply_theme(&theme_path)) {
+                Ok(()) => exit(0),
+                Err(e) => eprintln!("warning: {e}, writing the config instead"),
+            }
         }
+
+        let alacritty_cfg = settings.config_file()?;
+
+        let applied = apply_theme(&alacritty_cfg, &theme_path, apply_mode)?;
+        print_applied(&alacritty_cfg, applied);
+
+        exit(0);
     }
-}
 
-impl App {
-    fn run(&mut self, mut terminal: Terminal<impl Backend>) -> io::Result<()> {
-        loop {
-            self.draw(&mut terminal)?;
-
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    use KeyCode::*;
-                    match key.code {
-                        Char('q') | Esc => return Ok(()),
-                        Char('j') | Down => self.items.next(),
-                        Char('k') | Up => self.items.previous(),
-                        Char('g') => self.go_top(),
-                        Char('G') => self.go_bottom(),
-                        Char('a') => self.apply_theme(),
-                        _ => {}
-                    }
-                }
+    // load the themes before taking over the terminal so errors are printed normally
+    let ipc = if cli.window {
+        Some(Ipc::current_window()?)
+    } else if cli.ipc {
+        match Ipc::from_env(cli.all_windows) {
+            Ok(ipc) => Some(ipc),
+            Err(e) => {
+                eprintln!("warning: {e}, previewing through the config instead");
+                None
             }
         }
-    }
+    } else {
+        None
+    };
 
-    fn draw(&mut self, terminal: &mut Terminal<impl Backend>) -> io::Result<()> {
-        terminal.draw(|f| f.render_widget(self, f.size()))?;
-        Ok(())
-    }
-}
+    let mut app = App::new(
+        apply_mode,
+        &settings,
+        !cli.no_preview,
+        ipc,
+        cli.window,
+        cli.color_mode,
+    )?;
 
-impl Widget for &mut App {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        // Create a space for header, todo list and the footer.
-        let vertical = Layout::vertical([
-            Constraint::Length(2),
-            Constraint::Min(0),
-            Constraint::Length(2),
-        ]);
-        let [header_area, rest_area, footer_area] = vertical.areas(area);
-
-        // Create two chunks with equal vertical screen space. One for the list and the other for
-        // the info block.
-        let horizontal =
-            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]);
-        let [upper_item_list_area, lower_item_list_area] = horizontal.areas(rest_area);
-
-        render_title(header_area, buf);
-        self.render_todo(upper_item_list_area, buf);
-        self.render_info(lower_item_list_area, buf);
-        render_footer(footer_area, buf);
-    }
-}
+    init_error_hooks()?;
+    let terminal = init_terminal()?;
 
-impl App {
-    fn render_todo(&mut self, area: Rect, buf: &mut Buffer) {
-        // We create two blocks, one is for the header (outer) and the other is for list (inner).
-        let outer_block = Block::default()
-            .borders(Borders::NONE)
-            .fg(TEXT_COLOR)
-            .bg(TODO_HEADER_BG)
-            .title("Themes List")
-            .title_alignment(Alignment::Center);
-        let inner_block = Block::default()
-            .borders(Borders::NONE)
-            .fg(TEXT_COLOR)
-            .bg(NORMAL_ROW_COLOR);
-
-        // We get the inner area from outer_block. We'll use this area later to render the table.
-        let outer_area = area;
-        let inner_area = outer_block.inner(outer_area);
-
-        // We can render the header in outer_area.
-        outer_block.render(outer_area, buf);
-
-        // Iterate through all elements in the `items` and stylize them.
-        let items: Vec<Line> = self
-            .items
-            .items
-            .iter()
-            .map(|item| Line::styled(item.name.to_string(), TEXT_COLOR))
-            .collect();
-
-        // Create a List from all list items and highlight the currently selected one
-        let items = List::new(items)
-            .block(inner_block)
-            .highlight_style(
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .add_modifier(Modifier::REVERSED)
-                    .fg(SELECTED_STYLE_FG),
-            )
-            .highlight_symbol(">")
-            .highlight_spacing(HighlightSpacing::Always);
-
-        // We can now render the item list
-        // (look careful we are using StatefulWidget's render.)
-        // ratatui::widgets::StatefulWidget::render as stateful_render
-        StatefulWidget::render(items, inner_area, buf, &mut self.items.state);
-    }
+    // create app and run it, the terminal is restored before an error is printed
+    let result = app.run(terminal);
 
-    fn render_info(&self, area: Rect, buf: &mut Buffer) {
-        let theme_index = self.items.state.selected().unwrap_or(0);
-        let theme_path = self.items.items.get(theme_index).unwrap();
-        // get theme file and parse it
-        let theme_file_contents =
-            fs::read_to_string(&theme_path.path).expect("Failed to read theme"); // TODO: Remove .expect later
-
-        let theme_colors: YmlColor =
-            serde_yaml::from_str(&theme_file_contents).unwrap_or(YmlColor::default());
-        let colors = theme_colors.colors;
-        // TODO: Make fg visable no mater the bg color
-
-        let info: Vec<Line> = vec![
-            Line::from(vec![
-                Span::raw("name:"),
-                Span::styled(
-                    colors.name.unwrap_or("Empty".to_string()),
-                    Style::new().bold(),
-                ),
-            ]),
-            Line::from(vec![
-                Span::raw("author:"),
-                Span::styled(
-                    colors.author.unwrap_or("Empty".to_string()),
-                    Style::new().bold(),
-                ),
-            ]),
-            // ---
-            Line::from("primary:"),
-            Line::from(vec![
-                Span::raw("background:"),
-                Span::styled(
-                    colors.primary.background.clone(),
-                    Style::new()
-                        .bg(hex_to_rgb(colors.primary.background))
-                        .bold(),
-                ),
-            ]),
-            Line::from(vec![
-                Span::raw("foreground:"),
-                Span::styled(
-                    colors.primary.foreground.clone(),
-                    Style::new()
-                        .bg(hex_to_rgb(colors.primary.foreground))
-                        .bold(),
-                ),
-            ]),
-            // ---
-            Line::from("cursor:"),
-            Line::from(vec![
-                Span::raw("text:"),
-                Span::styled(
-                    match colors.cursor.clone() {
-                        Some(c) => c.text,
-                        None => "Empty".to_string(),
-                    },
-                    Style::new()
-                        .bg(hex_to_rgb(colors.cursor.clone().unwrap_or_default().text))
-                        .bold(),
-                ),
-            ]),
-            Line::from(vec![
-                Span::raw("cursor:"),
-                Span::styled(
-                    match colors.cursor.clone() {
-                        Some(c) => c.cursor,
-                        None => "Empty".to_string(),
-                    },
-                    Style::new()
-                        .bg(hex_to_rgb(colors.cursor.unwrap_or_default().cursor))
-                        .bold(),
-                ),
-            ]),
-            // ---
-            Line::from("normal:"),
-            Line::from(vec![
-                Span::raw("black:"),
-                Span::styled(
-                    colors.normal.black.clone(),
-                    Style::new().bg(hex_to_rgb(colors.normal.black)).bold(),
-                ),
-            ]),
-            Line::from(vec![
-                Span::raw("red:"),
-                Span::styled(
-                    colors.normal.red.clone(),
-                    Style::new().bg(hex_to_rgb(colors.normal.red)).bold(),
-                ),
-            ]),
-            Line::from(vec![
-                Span::raw("green:"),
-                Span::styled(
-                    colors.normal.green.clone(),
-                    Style::new().bg(hex_to_rgb(colors.normal.green)).bold(),
-                ),
-            ]),
-            Line::from(vec![
-                Span::raw("yellow:"),
-                Span::styled(
-                    colors.normal.yellow.clone(),
-                    Style::new().bg(hex_to_rgb(colors.normal.yellow)).bold(),
-                ),
-            ]),
-            Line::from(vec![
-                Span::raw("blue:"),
-                Span::styled(
-                    colors.normal.blue.clone(),
-                    Style::new().bg(hex_to_rgb(colors.normal.blue)).bold(),
-                ),
-            ]),
-            Line::from(vec![
-                Span::raw("magenta:"),
-                Span::styled(
-                    colors.normal.magenta.clone(),
-                    Style::new().bg(hex_to_rgb(colors.normal.magenta)).bold(),
-                ),
-            ]),
-            Line::from(vec![
-                Span::raw("cyan:"),
-                Span::styled(
-                    colors.normal.cyan.clone(),
-                    Style::new().bg(hex_to_rgb(colors.normal.cyan)).bold(),
-                ),
-            ]),
-            Line::from(vec![
-                Span::raw("white:"),
-                Span::styled(
-                    colors.normal.white.clone(),
-                    Style::new().bg(hex_to_rgb(colors.normal.white)).bold(),
-                ),
-            ]),
-            // ---
-            Line::from("bright:"),
-            Line::from(vec![
-                Span::raw("black:"),
-                Span::styled(
-                    colors.bright.black.clone(),
-                    Style::new().bg(hex_to_rgb(colors.bright.black)).bold(),
-                ),
-            ]),
-            Line::from(vec![
-                Span::raw("red:"),
-                Span::styled(
-                    colors.bright.red.clone(),
-                    Style::new().bg(hex_to_rgb(colors.bright.red)).bold(),
-                ),
-            ]),
-            Line::from(vec![
-                Span::raw("green:"),
-                Span::styled(
-                    colors.bright.green.clone(),
-                    Style::new().bg(hex_to_rgb(colors.bright.green)).bold(),
-                ),
-            ]),
-            Line::from(vec![
-                Span::raw("yellow:"),
-                Span::styled(
-                    colors.bright.yellow.clone(),
-                    Style::new().bg(hex_to_rgb(colors.bright.yellow)).bold(),
-                ),
-            ]),
-            Line::from(vec![
-                Span::raw("blue:"),
-                Span::styled(
-                    colors.bright.blue.clone(),
-                    Style::new().bg(hex_to_rgb(colors.bright.blue)).bold(),
-                ),
-            ]),
-            Line::from(vec![
-                Span::raw("magenta:"),
-                Span::styled(
-                    colors.bright.magenta.clone(),
-                    Style::new().bg(hex_to_rgb(colors.bright.magenta)).bold(),
-                ),
-            ]),
-            Line::from(vec![
-                Span::raw("cyan:"),
-                Span::styled(
-                    colors.bright.cyan.clone(),
-                    Style::new().bg(hex_to_rgb(colors.bright.cyan)).bold(),
-                ),
-            ]),
-            Line::from(vec![
-                Span::raw("white:"),
-                Span::styled(
-                    colors.bright.white.clone(),
-                    Style::new().bg(hex_to_rgb(colors.bright.white)).bold(),
-                ),
-            ]),
-            // ---
-        ];
-
-        // We show the list item's info under the list in this paragraph
-        let outer_info_block = Block::default()
-            .borders(Borders::NONE)
-            .fg(TEXT_COLOR)
-            .bg(TODO_HEADER_BG)
-            .title("Info")
-            .title_alignment(Alignment::Center);
-        let inner_info_block = Block::default()
-            .borders(Borders::NONE)
-            .bg(NORMAL_ROW_COLOR)
-            .padding(Padding::horizontal(1));
-
-        // This is a similar process to what we did for list. outer_info_area will be used for
-        // header inner_info_area will be used for the list info.
-        let outer_info_area = area;
-        let inner_info_area = outer_info_block.inner(outer_info_area);
-
-        // We can render the header. Inner info will be rendered later
-        outer_info_block.render(outer_info_area, buf);
-
-        let info_paragraph = Paragraph::new(info)
-            .block(inner_info_block)
-            .fg(TEXT_COLOR)
-            .wrap(Wrap { trim: false });
-
-        info_paragraph.render(inner_info_area, buf);
-    }
+    restore_terminal()?;
+
+    Ok(result?)
 }
@@ -0,0 +1,182 @@
+//! Converting yaml configs and themes to toml.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{yaml_to_toml, ConfigFormat};
+use crate::error::{read_file, write_file, Error};
+use crate::settings::expand_home;
+
+/// The path a migrated yaml file is kept at, `dracula.yml` is renamed to `dracula.yml.bak`.
+pub fn migrate_backup_path(yaml_path: &Path) -> PathBuf {
+    let mut name = yaml_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+
+    yaml_path.with_file_name(name)
+}
+
+/// The absolute path of a file, with the dir resolved so the same file is always written the same
+/// way. The file itself does not have to exist, migrated files are renamed.
+pub fn resolve_path(path: &Path) -> PathBuf {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let dir = fs::canonicalize(&dir).unwrap_or(dir);
+
+    match path.file_name() {
+        Some(name) => dir.join(name),
+        None => dir,
+    }
+}
+
+// the file an import of the config at `config_path` points at, alacritty expands `~` and resolves
+// relative imports against the dir of the config
+pub(crate) fn resolve_import(config_path: &Path, import: &str) -> PathBuf {
+    let path = expand_home(PathBuf::from(import));
+
+    if path.is_relative() {
+        resolve_path(&config_path.parent().unwrap_or(Path::new(".")).join(path))
+    } else {
+        resolve_path(&path)
+    }
+}
+
+/// A `.yml` or `.yaml` file, the files `migrate` converts.
+pub fn is_yaml(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("yml" | "yaml")
+    )
+}
+
+/// Convert a yaml file to toml, the yaml file is kept as [`migrate_backup_path`]. `import`s of
+/// yaml files in `migrated`, the [`resolve_path`]s of the files migrated before this one, are
+/// pointed at the toml files they were migrated to, every other import is left alone. Returns the
+/// path of the new toml file.
+pub fn migrate_file(yaml_path: &Path, migrated: &[PathBuf]) -> Result<PathBuf, Error> {
+    let toml_path = yaml_path.with_extension("toml");
+    let backup_path = migrate_backup_path(yaml_path);
+
+    for path in [&toml_path, &backup_path] {
+        if path.exists() {
+            return Err(Error::AlreadyExists(path.clone()));
+        }
+    }
+
+    let contents = read_file(yaml_path)?;
+    let yaml: serde_yaml::Value = ConfigFormat::Yaml.parse(yaml_path, &contents)?;
+
+    let mut table = match yaml_to_toml(&yaml) {
+        Some(toml::Value::Table(table)) => table,
+        None => toml::Table::new(),
+        Some(_) => {
+            return Err(Error::Parse {
+                path: yaml_path.to_path_buf(),
+                message: "top level is not a mapping".into(),
+            })
+        }
+    };
+
+    // only the imports that were migrated exist as toml, the others keep pointing at the yaml
+    if let Some(toml::Value::Array(imports)) = table.get_mut("import") {
+        for import in imports.iter_mut() {
+            if let toml::Value::String(import) = import {
+                let path = Path::new(import.as_str());
+
+                if is_yaml(path) && migrated.contains(&resolve_import(yaml_path, import)) {
+                    *import = path.with_extension("toml").to_string_lossy().into_owned();
+                }
+            }
+        }
+    }
+
+    let new_contents = toml::to_string(&table).map_err(|e| Error::Serialize {
+        path: toml_path.clone(),
+        message: e.to_string(),
+    })?;
+
+    write_file(&toml_path, &new_contents)?;
+    fs::rename(yaml_path, &backup_path).map_err(|source| Error::Io {
+        action: "rename",
+        path: yaml_path.to_path_buf(),
+        source,
+    })?;
+
+    Ok(toml_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a fresh dir for each test, tests run in parallel
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ats-migrate-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        // imports are compared by their resolved paths
+        dir.canonicalize().unwrap()
+    }
+
+    #[test]
+    fn yaml_is_kept_as_bak() {
+        let dir = temp_dir("bak");
+        let yaml_path = dir.join("alacritty.yml");
+        let contents = "window:\n  opacity: 0.9\nunset: ~\n";
+        fs::write(&yaml_path, contents).unwrap();
+
+        let toml_path = migrate_file(&yaml_path, &[]).unwrap();
+
+        assert_eq!(toml_path, dir.join("alacritty.toml"));
+        assert_eq!(
+            fs::read_to_string(&toml_path).unwrap(),
+            "[window]\nopacity = 0.9\n"
+        );
+        assert!(!yaml_path.exists());
+        assert_eq!(
+            fs::read_to_string(dir.join("alacritty.yml.bak")).unwrap(),
+            contents
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn existing_files_are_not_overwritten() {
+        let dir = temp_dir("exists");
+        let yaml_path = dir.join("alacritty.yml");
+        fs::write(&yaml_path, "window:\n  opacity: 0.9\n").unwrap();
+
+        for existing in ["alacritty.toml", "alacritty.yml.bak"] {
+            fs::write(dir.join(existing), "old").unwrap();
+
+            assert!(matches!(
+                migrate_file(&yaml_path, &[]),
+                Err(Error::AlreadyExists(path)) if path == dir.join(existing)
+            ));
+            assert_eq!(fs::read_to_string(dir.join(existing)).unwrap(), "old");
+            assert!(yaml_path.exists());
+            fs::remove_file(dir.join(existing)).unwrap();
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn only_migrated_imports_are_rewritten() {
+        let dir = temp_dir("imports");
+        let yaml_path = dir.join("alacritty.yml");
+        fs::write(
+            &yaml_path,
+            "import:\n  - colors.yml\n  - keys.yml\n  - fonts.toml\n",
+        )
+        .unwrap();
+
+        migrate_file(&yaml_path, &[dir.join("colors.yml")]).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.join("alacritty.toml")).unwrap(),
+            "import = [\"colors.toml\", \"keys.yml\", \"fonts.toml\"]\n"
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,152 @@
+// A fake terminal session drawn with the colors of a theme, to see how the theme looks in use.
+
+use alacritty_theme_switcher::color::{CellRgb, Rgb};
+use alacritty_theme_switcher::theme::YmlColors;
+use ratatui::prelude::*;
+
+use crate::tui::tui_color;
+
+// the number of lines of `sample_lines`, for sizing the pane
+pub const SAMPLE_HEIGHT: u16 = 20;
+
+// the ANSI palette, white is left out as the sample has no use for it
+struct Palette {
+    black: Color,
+    red: Color,
+    green: Color,
+    yellow: Color,
+    blue: Color,
+    magenta: Color,
+    cyan: Color,
+}
+
+impl Palette {
+    fn new(entries: [(&'static str, Rgb); 8]) -> Self {
+        let [black, red, green, yellow, blue, magenta, cyan, _] =
+            entries.map(|(_, color)| tui_color(color));
+
+        Self {
+            black,
+            red,
+            green,
+            yellow,
+            blue,
+            magenta,
+            cyan,
+        }
+    }
+}
+
+fn fg(color: Color) -> Style {
+    Style::new().fg(color)
+}
+
+// `user@host ~/src/project (main) $ command`
+fn prompt(normal: &Palette, command: &'static str) -> Line<'static> {
+    Line::from(vec![
+        Span::styled("user@host", fg(normal.green).bold()),
+        Span::raw(" "),
+        Span::styled("~/src/project", fg(normal.blue).bold()),
+        Span::styled(" (main)", fg(normal.yellow)),
+        Span::raw(" $ "),
+        Span::raw(command),
+    ])
+}
+
+/// The sample session, `ls --color` output, a diff, some highlighted code and the 16 colors.
+pub fn sample_lines(colors: &YmlColors) -> Vec<Line<'static>> {
+    let normal = Palette::new(colors.normal.entries());
+    let bright = Palette::new(colors.bright.entries());
+    let cursor = match colors.cursor.as_ref().and_then(|cursor| cursor.cursor) {
+        Some(CellRgb::Rgb(rgb)) => tui_color(rgb),
+        _ => tui_color(colors.primary.foreground),
+    };
+
+    let mut lines = vec![
+        prompt(&normal, "ls --color"),
+        Line::from(vec![
+            Span::raw("Cargo.toml  README.md  "),
+            Span::styled("src", fg(normal.blue).bold()),
+            Span::raw("  "),
+            Span::styled("target", fg(normal.blue).bold()),
+            Span::raw("  "),
+            Span::styled("build.sh", fg(normal.green).bold()),
+            Span::raw("  "),
+            Span::styled("latest", fg(normal.cyan).bold()),
+            Span::raw("  "),
+            Span::styled("site.tar.gz", fg(normal.red).bold()),
+        ]),
+        prompt(&normal, "git diff"),
+        Line::styled(
+            "diff --git a/src/main.rs b/src/main.rs",
+            Style::new().bold(),
+        ),
+        Line::styled("@@ -1,3 +1,3 @@ fn main() {", fg(normal.cyan)),
+        Line::raw("     let count = 42;"),
+        Line::styled("-    println!(\"hello\");", fg(normal.red)),
+        Line::styled("+    println!(\"hello {count}\");", fg(normal.green)),
+        prompt(&normal, "cat src/main.rs"),
+        Line::from(vec![
+            Span::styled("fn", fg(normal.magenta)),
+            Span::raw(" "),
+            Span::styled("main", fg(normal.blue)),
+            Span::raw("() {"),
+        ]),
+        Line::styled("    // greet the user", fg(bright.black).italic()),
+        Line::from(vec![
+            Span::raw("    "),
+            Span::styled("let", fg(normal.magenta)),
+            Span::raw(" count: "),
+            Span::styled("u32", fg(normal.yellow)),
+            Span::raw(" = "),
+            Span::styled("42", fg(normal.cyan)),
+            Span::raw(";"),
+        ]),
+        Line::from(vec![
+            Span::raw("    "),
+            Span::styled("println!", fg(normal.blue)),
+            Span::raw("("),
+            Span::styled("\"hello {count}\"", fg(normal.green)),
+            Span::raw(");"),
+        ]),
+        Line::raw("}"),
+        prompt(&normal, "cargo build"),
+        Line::from(vec![
+            Span::styled("error", fg(bright.red).bold()),
+            Span::raw(": "),
+            Span::styled("warning", fg(bright.yellow).bold()),
+            Span::raw(": "),
+            Span::styled("note", fg(bright.cyan).bold()),
+            Span::raw(": "),
+            Span::styled("Finished", fg(bright.green).bold()),
+        ]),
+        Line::from(
+            [
+                prompt(&normal, "").spans,
+                vec![Span::styled(" ", Style::new().bg(cursor))],
+            ]
+            .concat(),
+        ),
+        Line::raw(""),
+    ];
+
+    // the 16 colors as numbered blocks, normal on the first line and bright on the second
+    for (offset, entries) in [(0, colors.normal.entries()), (8, colors.bright.entries())] {
+        lines.push(Line::from(
+            entries
+                .iter()
+                .enumerate()
+                .map(|(i, (_, color))| {
+                    Span::styled(
+                        format!(" {:>2} ", offset + i),
+                        Style::new()
+                            .bg(tui_color(*color))
+                            .fg(tui_color(color.contrasting_label())),
+                    )
+                })
+                .collect::<Vec<_>>(),
+        ));
+    }
+
+    lines
+}
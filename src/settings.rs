@@ -0,0 +1,119 @@
+//! The settings of the switcher itself, read from
+//! `$XDG_CONFIG_HOME/alacritty-theme-switcher/config.toml`.
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::config::{config_home, find_alacritty_config_file, ConfigFormat};
+use crate::error::{read_file, Error};
+use crate::store::{default_sources, get_themes_dir, ThemeSource, ThemeStore, USER_SOURCE};
+
+/// Overrides for the paths that are discovered otherwise. The command line and environment
+/// take precedence over the settings file, see [`Settings::merge`].
+#[derive(Default, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Settings {
+    /// The alacritty config to write themes to.
+    pub config: Option<PathBuf>,
+    /// The dir to read themes from.
+    pub themes_dir: Option<PathBuf>,
+    /// More theme dirs, merged with the themes dir.
+    #[serde(default)]
+    pub sources: Vec<ThemeSource>,
+}
+
+/// The path of the settings file.
+pub fn settings_file() -> Result<PathBuf, Error> {
+    Ok(config_home()?
+        .join("alacritty-theme-switcher")
+        .join("config.toml"))
+}
+
+// `~/` is expanded so the settings file can be shared between machines
+pub(crate) fn expand_home(path: PathBuf) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => path,
+    }
+}
+
+impl Settings {
+    /// Read the settings file, a missing file is the same as an empty one.
+    pub fn load() -> Result<Self, Error> {
+        let path = settings_file()?;
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Self::load_from(&path)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, Error> {
+        let settings: Settings = ConfigFormat::Toml.parse(path, &read_file(path)?)?;
+
+        Ok(Self {
+            config: settings.config.map(expand_home),
+            themes_dir: settings.themes_dir.map(expand_home),
+            sources: settings
+                .sources
+                .into_iter()
+                .map(|source| ThemeSource {
+                    dir: expand_home(source.dir),
+                    ..source
+                })
+                .collect(),
+        })
+    }
+
+    /// Replace the settings with the ones set in `other`.
+    pub fn merge(mut self, other: Settings) -> Self {
+        if other.config.is_some() {
+            self.config = other.config;
+        }
+        if other.themes_dir.is_some() {
+            self.themes_dir = other.themes_dir;
+        }
+        self.sources.extend(other.sources);
+
+        self
+    }
+
+    /// The configured alacritty config, or the one alacritty would use.
+    pub fn config_file(&self) -> Result<PathBuf, Error> {
+        match &self.config {
+            Some(path) if path.is_file() => Ok(path.clone()),
+            Some(path) => Err(Error::ConfigNotFound {
+                tried: vec![path.clone()],
+            }),
+            None => find_alacritty_config_file(),
+        }
+    }
+
+    /// The configured themes dir, or the default one.
+    pub fn themes_dir(&self) -> Result<PathBuf, Error> {
+        match &self.themes_dir {
+            Some(dir) => Ok(dir.clone()),
+            None => get_themes_dir(),
+        }
+    }
+
+    /// The store of the themes dir and the configured sources, without the system themes that
+    /// the user doesn't maintain.
+    pub fn own_theme_store(&self) -> Result<ThemeStore, Error> {
+        let mut sources = vec![ThemeSource::new(USER_SOURCE, self.themes_dir()?, 100)];
+        sources.extend(self.sources.iter().cloned());
+
+        Ok(ThemeStore::with_sources(sources))
+    }
+
+    /// The store of the themes dir, the system themes and the configured sources.
+    pub fn theme_store(&self) -> Result<ThemeStore, Error> {
+        let mut sources = default_sources(self.themes_dir()?);
+        // before the system themes, so the configured sources win a tie with them
+        sources.splice(1..1, self.sources.iter().cloned());
+
+        Ok(ThemeStore::with_sources(sources))
+    }
+}
@@ -0,0 +1,535 @@
+//! Listing and looking up the themes in the theme dirs.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::config::config_home;
+use crate::error::Error;
+
+/// File extensions a theme can have, the first one is tried first when looking up a theme by
+/// name.
+pub const THEME_EXTENSIONS: [&str; 3] = ["toml", "yml", "yaml"];
+
+/// Where distributions install the themes of alacritty-theme.
+pub const SYSTEM_THEMES_DIR: &str = "/usr/share/alacritty/themes";
+
+/// Name of the source of the user's own themes dir.
+pub const USER_SOURCE: &str = "user";
+
+/// Name of the source of [`SYSTEM_THEMES_DIR`].
+pub const SYSTEM_SOURCE: &str = "system";
+
+/// The default themes dir, `$XDG_CONFIG_HOME/alacritty/themes` or `~/.config/alacritty/themes`
+/// when `XDG_CONFIG_HOME` is not set.
+pub fn get_themes_dir() -> Result<PathBuf, Error> {
+    let mut dir = config_home()?;
+
+    dir.push("alacritty");
+    dir.push("themes");
+
+    Ok(dir)
+}
+
+/// A dir themes are read from.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeSource {
+    /// Shown next to the themes of this source and used to pick a theme from it, as in
+    /// `source:theme`.
+    pub name: String,
+    pub dir: PathBuf,
+    /// When two sources have a theme with the same name the one with the higher priority wins,
+    /// on a tie the source listed first wins. Defaults to 50, between the [`SYSTEM_SOURCE`] (0)
+    /// and the [`USER_SOURCE`] (100).
+    #[serde(default = "default_priority")]
+    pub priority: i32,
+}
+
+fn default_priority() -> i32 {
+    50
+}
+
+impl ThemeSource {
+    pub fn new(name: impl Into<String>, dir: impl Into<PathBuf>, priority: i32) -> Self {
+        Self {
+            name: name.into(),
+            dir: dir.into(),
+            priority,
+        }
+    }
+
+    /// Whether the dir of the source exists. Only a missing themes dir of the [`USER_SOURCE`] is
+    /// an error, other sources are skipped when they are missing.
+    pub fn is_available(&self) -> bool {
+        self.dir.is_dir()
+    }
+
+    // every theme file in the dir of the source and its subdirs
+    fn themes(&self) -> Result<Vec<ThemeEntry>, Error> {
+        if !self.dir.exists() {
+            return Err(Error::ThemesDirNotFound(self.dir.clone()));
+        }
+
+        let mut themes_list: Vec<ThemeEntry> = Vec::new();
+        let mut dirs: Vec<PathBuf> = vec![self.dir.clone()];
+        // symlinked dirs are followed once, a link to a parent dir would loop forever
+        let mut visited: HashSet<PathBuf> = HashSet::new();
+
+        while let Some(dir) = dirs.pop() {
+            let Ok(real_dir) = dir.canonicalize() else {
+                continue;
+            };
+            if !visited.insert(real_dir) {
+                continue;
+            }
+
+            // a subdir that can't be read is skipped like one that can't be resolved, only the
+            // source dir itself has to be readable
+            let entries = match dir.read_dir() {
+                Ok(entries) => entries,
+                Err(_) if dir != self.dir => continue,
+                Err(source) => {
+                    return Err(Error::Io {
+                        action: "read",
+                        path: dir,
+                        source,
+                    })
+                }
+            };
+
+            for entry in entries.filter_map(Result::ok) {
+                let path = entry.path();
+                let name = entry_name(&path);
+
+                // `.git`, editor swap files and the like
+                if name.starts_with('.') {
+                    continue;
+                }
+
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if is_theme_file(&path) {
+                    themes_list.push(ThemeEntry {
+                        name,
+                        group: path
+                            .parent()
+                            .and_then(|parent| parent.strip_prefix(&self.dir).ok())
+                            .map(Path::to_path_buf)
+                            .unwrap_or_default(),
+                        path,
+                        source: self.name.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(themes_list)
+    }
+
+    // the path of the theme called `name` in this source. The extension can be left out and
+    // themes in subdirs are found by their file name, or by their path relative to the dir
+    fn find(&self, name: &str) -> Result<Option<PathBuf>, Error> {
+        let name = Path::new(name);
+        let has_extension = name
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| THEME_EXTENSIONS.contains(&ext));
+
+        let matches = |theme: &ThemeEntry| {
+            let relative = theme.group.join(&theme.name);
+            let relative = if has_extension {
+                relative
+            } else {
+                relative.with_extension("")
+            };
+
+            relative == name || relative.file_name() == Some(name.as_os_str())
+        };
+
+        let mut found: Vec<ThemeEntry> = self.themes()?.into_iter().filter(matches).collect();
+
+        // the least nested theme first, then by the order of the extensions
+        found.sort_by_key(|theme| {
+            (
+                theme.group.components().count(),
+                theme.extension_index(),
+                theme.path.clone(),
+            )
+        });
+
+        Ok(found.into_iter().next().map(|theme| theme.path))
+    }
+}
+
+// a file with one of the theme extensions, that is not a readme
+fn is_theme_file(path: &Path) -> bool {
+    let is_readme = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| stem.eq_ignore_ascii_case("readme"));
+
+    !is_readme
+        && path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| THEME_EXTENSIONS.contains(&ext))
+}
+
+/// A file in one of the theme dirs.
+#[derive(Clone, Debug)]
+pub struct ThemeEntry {
+    /// The file name, including the extension.
+    pub name: String,
+    pub path: PathBuf,
+    /// The subdir of the source dir the file is in, empty for the top level.
+    pub group: PathBuf,
+    /// The name of the [`ThemeSource`] the file is from.
+    pub source: String,
+}
+
+impl ThemeEntry {
+    /// The file name without the extension, themes with the same stem collide.
+    pub fn stem(&self) -> &str {
+        self.path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or(&self.name)
+    }
+
+    // the position of the extension in THEME_EXTENSIONS
+    fn extension_index(&self) -> usize {
+        let extension = self.path.extension().and_then(|ext| ext.to_str());
+
+        THEME_EXTENSIONS
+            .iter()
+            .position(|ext| Some(*ext) == extension)
+            .unwrap_or(THEME_EXTENSIONS.len())
+    }
+}
+
+// the file name for display, names that are not utf-8 get replacement characters
+fn entry_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// The themes of one or more theme dirs, merged into one catalog.
+#[derive(Clone, Debug)]
+pub struct ThemeStore {
+    // sorted by priority, highest first
+    sources: Vec<ThemeSource>,
+}
+
+impl ThemeStore {
+    /// A store of a single dir.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self::with_sources(vec![ThemeSource::new(USER_SOURCE, dir, 0)])
+    }
+
+    pub fn with_sources(mut sources: Vec<ThemeSource>) -> Self {
+        // the sort is stable, so on a tie the source listed first stays first
+        sources.sort_by_key(|source| std::cmp::Reverse(source.priority));
+
+        Self { sources }
+    }
+
+    /// The store of the default themes dir, see [`get_themes_dir`], and of
+    /// [`SYSTEM_THEMES_DIR`] when it exists.
+    pub fn open_default() -> Result<Self, Error> {
+        Ok(Self::with_sources(default_sources(get_themes_dir()?)))
+    }
+
+    /// The sources, highest priority first.
+    pub fn sources(&self) -> &[ThemeSource] {
+        &self.sources
+    }
+
+    /// The dir of the highest priority source.
+    pub fn dir(&self) -> &Path {
+        &self.sources[0].dir
+    }
+
+    /// The sources other than the [`USER_SOURCE`] whose dir is missing, they are skipped by
+    /// [`ThemeStore::themes`] and [`ThemeStore::find`].
+    pub fn unavailable_sources(&self) -> impl Iterator<Item = &ThemeSource> {
+        self.sources
+            .iter()
+            .filter(|source| source.name != USER_SOURCE && !source.is_available())
+    }
+
+    // the sources that are read, a missing user themes dir is still reported as an error
+    fn available_sources(&self) -> impl Iterator<Item = &ThemeSource> {
+        self.sources
+            .iter()
+            .filter(|source| source.name == USER_SOURCE || source.is_available())
+    }
+
+    /// Every theme file in the theme dirs and their subdirs, dotfiles and readmes are skipped.
+    /// A theme that is also in a source with a higher priority is left out, files with the same
+    /// name in one source are all kept.
+    pub fn themes(&self) -> Result<Vec<ThemeEntry>, Error> {
+        let mut themes_list: Vec<ThemeEntry> = Vec::new();
+
+        for source in self.available_sources() {
+            for theme in source.themes()? {
+                let shadowed = themes_list
+                    .iter()
+                    .any(|other| other.source != theme.source && other.stem() == theme.stem());
+
+                if !shadowed {
+                    themes_list.push(theme);
+                }
+            }
+        }
+
+        Ok(themes_list)
+    }
+
+    /// The paths of the theme files, sorted.
+    pub fn theme_files(&self) -> Result<Vec<PathBuf>, Error> {
+        let mut themes: Vec<PathBuf> = self.themes()?.into_iter().map(|theme| theme.path).collect();
+
+        themes.sort();
+
+        Ok(themes)
+    }
+
+    /// Find a theme by its file name or its path in the theme dir, the extension can be left
+    /// out. The theme of the source with the highest priority is returned, `source:name` looks
+    /// only in that source.
+    pub fn find(&self, name: &str) -> Result<PathBuf, Error> {
+        let (sources, theme_name): (Vec<&ThemeSource>, &str) = match name.split_once(':') {
+            Some((source_name, theme_name))
+                if self.sources.iter().any(|source| source.name == source_name) =>
+            {
+                (
+                    self.available_sources()
+                        .filter(|source| source.name == source_name)
+                        .collect(),
+                    theme_name,
+                )
+            }
+            _ => (self.available_sources().collect(), name),
+        };
+
+        for source in sources {
+            if let Some(path) = source.find(theme_name)? {
+                return Ok(path);
+            }
+        }
+
+        Err(Error::ThemeNotFound(name.to_string()))
+    }
+}
+
+/// The user's themes dir and, when it exists, [`SYSTEM_THEMES_DIR`] with a lower priority.
+pub fn default_sources(user_dir: PathBuf) -> Vec<ThemeSource> {
+    let mut sources = vec![ThemeSource::new(USER_SOURCE, user_dir, 100)];
+
+    if Path::new(SYSTEM_THEMES_DIR).is_dir() {
+        sources.push(ThemeSource::new(SYSTEM_SOURCE, SYSTEM_THEMES_DIR, 0));
+    }
+
+    sources
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    // a dir with an empty file for each of `files`, tests run in parallel
+    fn themes_dir(name: &str, files: &[&str]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ats-store-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        for file in files {
+            let path = dir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        dir
+    }
+
+    // the themes as `source:group/name`, sorted
+    fn listed(store: &ThemeStore) -> Vec<String> {
+        let mut themes: Vec<String> = store
+            .themes()
+            .unwrap()
+            .iter()
+            .map(|theme| {
+                format!(
+                    "{}:{}",
+                    theme.source,
+                    theme.group.join(&theme.name).display()
+                )
+            })
+            .collect();
+        themes.sort();
+        themes
+    }
+
+    #[test]
+    fn higher_priority_shadows() {
+        let user = themes_dir("shadow-user", &["dracula.toml"]);
+        let team = themes_dir(
+            "shadow-team",
+            &["dracula.toml", "nord.yml", "sub/dracula.yml"],
+        );
+        let store = ThemeStore::with_sources(vec![
+            ThemeSource::new("team", &team, 50),
+            ThemeSource::new(USER_SOURCE, &user, 100),
+        ]);
+
+        assert_eq!(store.dir(), user);
+        assert_eq!(listed(&store), ["team:nord.yml", "user:dracula.toml"]);
+        assert_eq!(store.find("dracula").unwrap(), user.join("dracula.toml"));
+        assert_eq!(store.find("nord").unwrap(), team.join("nord.yml"));
+        assert_eq!(
+            store.find("team:dracula").unwrap(),
+            team.join("dracula.toml")
+        );
+        assert!(matches!(
+            store.find("user:nord"),
+            Err(Error::ThemeNotFound(name)) if name == "user:nord"
+        ));
+
+        fs::remove_dir_all(user).unwrap();
+        fs::remove_dir_all(team).unwrap();
+    }
+
+    #[test]
+    fn first_listed_source_wins_a_tie() {
+        let first = themes_dir("tie-first", &["dracula.toml"]);
+        let second = themes_dir("tie-second", &["dracula.toml"]);
+        let store = ThemeStore::with_sources(vec![
+            ThemeSource::new("first", &first, 50),
+            ThemeSource::new("second", &second, 50),
+        ]);
+
+        assert_eq!(listed(&store), ["first:dracula.toml"]);
+        assert_eq!(store.find("dracula").unwrap(), first.join("dracula.toml"));
+
+        fs::remove_dir_all(first).unwrap();
+        fs::remove_dir_all(second).unwrap();
+    }
+
+    #[test]
+    fn same_name_in_one_source_is_kept() {
+        let dir = themes_dir("same-source", &["dracula.toml", "sub/dracula.yml"]);
+        let store = ThemeStore::new(&dir);
+
+        assert_eq!(
+            listed(&store),
+            ["user:dracula.toml", "user:sub/dracula.yml"]
+        );
+        // the least nested one
+        assert_eq!(store.find("dracula").unwrap(), dir.join("dracula.toml"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn missing_sources_are_skipped() {
+        let user = themes_dir("missing-user", &["dracula.toml"]);
+        let missing = user.join("missing");
+        let store = ThemeStore::with_sources(vec![
+            ThemeSource::new(USER_SOURCE, &user, 100),
+            ThemeSource::new("team", &missing, 50),
+        ]);
+
+        assert_eq!(listed(&store), ["user:dracula.toml"]);
+        assert_eq!(
+            store
+                .unavailable_sources()
+                .map(|source| source.name.as_str())
+                .collect::<Vec<_>>(),
+            ["team"]
+        );
+        assert!(matches!(
+            store.find("team:dracula"),
+            Err(Error::ThemeNotFound(_))
+        ));
+
+        // the user's own dir has to exist
+        assert!(matches!(
+            ThemeStore::new(&missing).themes(),
+            Err(Error::ThemesDirNotFound(_))
+        ));
+
+        fs::remove_dir_all(user).unwrap();
+    }
+
+    #[test]
+    fn subdirs_are_scanned() {
+        let dir = themes_dir(
+            "scan",
+            &[
+                "dracula.toml",
+                "README.md",
+                "readme.toml",
+                ".hidden.toml",
+                ".git/config.toml",
+                "notes.txt",
+                "alacritty-theme/themes/nord.toml",
+                "alacritty-theme/themes/gruvbox_dark.yaml",
+            ],
+        );
+        let store = ThemeStore::new(&dir);
+
+        assert_eq!(
+            listed(&store),
+            [
+                "user:alacritty-theme/themes/gruvbox_dark.yaml",
+                "user:alacritty-theme/themes/nord.toml",
+                "user:dracula.toml",
+            ]
+        );
+
+        let nord = dir.join("alacritty-theme/themes/nord.toml");
+        assert_eq!(store.find("nord").unwrap(), nord);
+        assert_eq!(store.find("nord.toml").unwrap(), nord);
+        assert_eq!(store.find("alacritty-theme/themes/nord").unwrap(), nord);
+        assert!(store.find("themes/nord").is_err());
+        assert!(store.find("nord.yml").is_err());
+        assert!(store.find("hidden").is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_loops_end() {
+        let dir = themes_dir("symlinks", &["sub/dracula.toml"]);
+        std::os::unix::fs::symlink("..", dir.join("sub/loop")).unwrap();
+
+        assert_eq!(listed(&ThemeStore::new(&dir)), ["user:sub/dracula.toml"]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unreadable_subdirs_are_skipped() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = themes_dir("unreadable", &["dracula.toml", "locked/nord.toml"]);
+        let locked = dir.join("locked");
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        // root can read it anyway
+        if fs::read_dir(&locked).is_err() {
+            assert_eq!(listed(&ThemeStore::new(&dir)), ["user:dracula.toml"]);
+        }
+
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
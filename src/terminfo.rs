@@ -0,0 +1,300 @@
+// Reading the color capabilities of a terminal from its compiled terminfo entry, the format is
+// described in term(5) of ncurses.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+// the magic numbers of entries with 16 and 32 bit numbers
+const MAGIC_16BIT: i16 = 0o432;
+const MAGIC_32BIT: i16 = 0o1036;
+
+// the position of `colors` in the numbers of the standard capabilities
+const COLORS_INDEX: usize = 13;
+
+// what the entry of a terminal says about its colors
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ColorCapabilities {
+    // the `colors` number, `None` when it is missing
+    pub colors: Option<u32>,
+    // the `Tc` or `RGB` extended capability, 24-bit colors can be set directly
+    pub direct: bool,
+}
+
+// the dirs ncurses looks up entries in, in its order
+fn search_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if let Some(dir) = env::var_os("TERMINFO") {
+        dirs.push(PathBuf::from(dir));
+    }
+    if let Some(home) = env::var_os("HOME") {
+        dirs.push(PathBuf::from(home).join(".terminfo"));
+    }
+    if let Ok(terminfo_dirs) = env::var("TERMINFO_DIRS") {
+        // an empty entry stands for the default dirs, which are added below anyway
+        dirs.extend(
+            terminfo_dirs
+                .split(':')
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from),
+        );
+    }
+    dirs.extend(
+        [
+            "/etc/terminfo",
+            "/lib/terminfo",
+            "/usr/share/terminfo",
+            "/usr/lib/terminfo",
+            "/usr/local/share/terminfo",
+        ]
+        .map(PathBuf::from),
+    );
+
+    dirs
+}
+
+// the compiled entry of `term`, in a subdir named after its first letter, or the hex code of the
+// letter on macOS
+fn read_entry(term: &str) -> Option<Vec<u8>> {
+    let first = term.chars().next()?;
+
+    if term.contains(['/', '\\']) || term.starts_with('.') {
+        return None;
+    }
+
+    search_dirs().into_iter().find_map(|dir| {
+        [first.to_string(), format!("{:02x}", first as u32)]
+            .iter()
+            .find_map(|subdir| fs::read(dir.join(subdir).join(term)).ok())
+    })
+}
+
+// the color capabilities of `term`, `None` when it has no entry that can be read
+pub fn color_capabilities(term: &str) -> Option<ColorCapabilities> {
+    parse(&read_entry(term)?)
+}
+
+// a cursor over the little endian values of an entry
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.position..self.position.checked_add(len)?)?;
+        self.position += len;
+        Some(bytes)
+    }
+
+    fn i16(&mut self) -> Option<i16> {
+        let bytes = self.bytes(2)?;
+        Some(i16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn number(&mut self, wide: bool) -> Option<i32> {
+        if wide {
+            let bytes = self.bytes(4)?;
+            Some(i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        } else {
+            self.i16().map(i32::from)
+        }
+    }
+
+    // a count from a header, negative counts are invalid
+    fn count(&mut self) -> Option<usize> {
+        usize::try_from(self.i16()?).ok()
+    }
+
+    // numbers and string offsets start on an even byte
+    fn align(&mut self) {
+        self.position += self.position % 2;
+    }
+}
+
+// the NUL terminated string at `offset` of a string table
+fn table_string(table: &[u8], offset: i16) -> Option<&[u8]> {
+    let rest = table.get(usize::try_from(offset).ok()?..)?;
+    let end = rest.iter().position(|&b| b == 0)?;
+
+    Some(&rest[..end])
+}
+
+// the color capabilities of a compiled entry
+pub fn parse(data: &[u8]) -> Option<ColorCapabilities> {
+    let mut reader = Reader { data, position: 0 };
+
+    let wide = match reader.i16()? {
+        MAGIC_16BIT => false,
+        MAGIC_32BIT => true,
+        _ => return None,
+    };
+    let names_size = reader.count()?;
+    let bools = reader.count()?;
+    let numbers = reader.count()?;
+    let strings = reader.count()?;
+    let table_size = reader.count()?;
+
+    reader.bytes(names_size)?;
+    reader.bytes(bools)?;
+    reader.align();
+
+    let mut capabilities = ColorCapabilities::default();
+    for i in 0..numbers {
+        let number = reader.number(wide)?;
+
+        if i == COLORS_INDEX && number >= 0 {
+            capabilities.colors = Some(number as u32);
+        }
+    }
+
+    reader.bytes(strings * 2)?;
+    reader.bytes(table_size)?;
+    reader.align();
+
+    // entries without extended capabilities end here
+    if reader.position >= data.len() {
+        return Some(capabilities);
+    }
+
+    let ext_bools = reader.count()?;
+    let ext_numbers = reader.count()?;
+    let ext_strings = reader.count()?;
+    let _ext_items = reader.count()?;
+    let ext_table_size = reader.count()?;
+
+    let bool_values = reader.bytes(ext_bools)?;
+    reader.align();
+    let number_values: Vec<i32> = (0..ext_numbers)
+        .map(|_| reader.number(wide))
+        .collect::<Option<_>>()?;
+    let string_offsets: Vec<i16> = (0..ext_strings)
+        .map(|_| reader.i16())
+        .collect::<Option<_>>()?;
+    let name_offsets: Vec<i16> = (0..ext_bools + ext_numbers + ext_strings)
+        .map(|_| reader.i16())
+        .collect::<Option<_>>()?;
+    let table = reader.bytes(ext_table_size)?;
+
+    // the names follow the string values in the table
+    let names_start = string_offsets
+        .iter()
+        .filter_map(|&offset| Some(offset as usize + table_string(table, offset)?.len() + 1))
+        .max()
+        .unwrap_or(0);
+    let names = &table[names_start.min(table.len())..];
+    let name = |i: usize| table_string(names, name_offsets[i]);
+
+    // `RGB` can be a boolean, a number or a string, any of them means direct colors
+    let is_direct = |name: Option<&[u8]>| matches!(name, Some(b"Tc" | b"RGB"));
+
+    capabilities.direct = (0..ext_bools).any(|i| bool_values[i] == 1 && is_direct(name(i)))
+        || (0..ext_numbers).any(|i| number_values[i] >= 0 && is_direct(name(ext_bools + i)))
+        || (0..ext_strings)
+            .any(|i| string_offsets[i] >= 0 && is_direct(name(ext_bools + ext_numbers + i)));
+
+    Some(capabilities)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a compiled entry with the `colors` number and extended booleans
+    fn entry(wide: bool, colors: i32, ext_bools: &[&str]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let push_i16 = |data: &mut Vec<u8>, value: i16| data.extend(value.to_le_bytes());
+        let names = b"test|a test terminal\0";
+        let number_count = COLORS_INDEX + 1;
+
+        push_i16(&mut data, if wide { MAGIC_32BIT } else { MAGIC_16BIT });
+        push_i16(&mut data, names.len() as i16);
+        push_i16(&mut data, 1);
+        push_i16(&mut data, number_count as i16);
+        push_i16(&mut data, 1);
+        push_i16(&mut data, 2);
+        data.extend(names);
+        data.push(1);
+        if data.len() % 2 == 1 {
+            data.push(0);
+        }
+        for i in 0..number_count {
+            let number = if i == COLORS_INDEX { colors } else { -1 };
+            if wide {
+                data.extend(number.to_le_bytes());
+            } else {
+                push_i16(&mut data, number as i16);
+            }
+        }
+        push_i16(&mut data, 0);
+        data.extend(b"x\0");
+
+        if ext_bools.is_empty() {
+            return data;
+        }
+        if data.len() % 2 == 1 {
+            data.push(0);
+        }
+
+        let table: Vec<u8> = ext_bools
+            .iter()
+            .flat_map(|name| name.bytes().chain([0]))
+            .collect();
+        push_i16(&mut data, ext_bools.len() as i16);
+        push_i16(&mut data, 0);
+        push_i16(&mut data, 0);
+        push_i16(&mut data, ext_bools.len() as i16);
+        push_i16(&mut data, table.len() as i16);
+        data.extend(ext_bools.iter().map(|_| 1));
+        if data.len() % 2 == 1 {
+            data.push(0);
+        }
+        let mut offset = 0;
+        for name in ext_bools {
+            push_i16(&mut data, offset);
+            offset += name.len() as i16 + 1;
+        }
+        data.extend(table);
+
+        data
+    }
+
+    #[test]
+    fn colors_number() {
+        assert_eq!(
+            parse(&entry(false, 256, &[])),
+            Some(ColorCapabilities {
+                colors: Some(256),
+                direct: false
+            })
+        );
+        assert_eq!(
+            parse(&entry(true, 0x1000000, &[])),
+            Some(ColorCapabilities {
+                colors: Some(0x1000000),
+                direct: false
+            })
+        );
+        assert_eq!(parse(&entry(false, -1, &[])).unwrap().colors, None);
+    }
+
+    #[test]
+    fn direct_color_flags() {
+        assert!(parse(&entry(false, 256, &["AX", "Tc"])).unwrap().direct);
+        assert!(parse(&entry(true, 256, &["RGB"])).unwrap().direct);
+        assert!(!parse(&entry(false, 256, &["AX", "XT"])).unwrap().direct);
+    }
+
+    #[test]
+    fn invalid_entries() {
+        assert_eq!(parse(b""), None);
+        assert_eq!(parse(&[0, 0, 1, 2, 3, 4]), None);
+
+        let mut truncated = entry(false, 256, &["Tc"]);
+        truncated.truncate(truncated.len() - 4);
+        assert_eq!(parse(&truncated), None);
+    }
+}
@@ -0,0 +1,258 @@
+//! The theme model, the `colors` section of alacritty's config.
+
+use std::path::Path;
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::color::{CellRgb, Rgb};
+use crate::config::ConfigFormat;
+use crate::error::{read_file, Error};
+
+/// The default background and foreground colors.
+#[derive(Default, Serialize, Deserialize)]
+pub struct YmlPrimary {
+    pub background: Rgb,
+    pub foreground: Rgb,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dim_foreground: Option<Rgb>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bright_foreground: Option<Rgb>,
+}
+
+/// Colors of the cursor and the text under it, a missing color is alacritty's default.
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct YmlCursor {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<CellRgb>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<CellRgb>,
+}
+
+/// Colors of search matches and hints, a missing color is alacritty's default.
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct YmlForegroundBackground {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub foreground: Option<CellRgb>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background: Option<CellRgb>,
+}
+
+// alacritty writes an unset color of the footer bar and line indicator as `None`
+fn rgb_or_none<'de, D>(deserializer: D) -> Result<Option<Rgb>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(color) if color != "None" => color.parse().map(Some).map_err(de::Error::custom),
+        _ => Ok(None),
+    }
+}
+
+/// Colors of the footer bar and the line indicator. A missing or `None` color follows the
+/// primary colors.
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct YmlBarColors {
+    #[serde(
+        default,
+        deserialize_with = "rgb_or_none",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub foreground: Option<Rgb>,
+    #[serde(
+        default,
+        deserialize_with = "rgb_or_none",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub background: Option<Rgb>,
+}
+
+/// Colors of selected text, a missing color is alacritty's default.
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct YmlSelection {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<CellRgb>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background: Option<CellRgb>,
+}
+
+/// Colors of search matches.
+#[derive(Default, Serialize, Deserialize)]
+pub struct YmlSearch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matches: Option<YmlForegroundBackground>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focused_match: Option<YmlForegroundBackground>,
+}
+
+/// Colors of the keyboard hint labels.
+#[derive(Default, Serialize, Deserialize)]
+pub struct YmlHints {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<YmlForegroundBackground>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<YmlForegroundBackground>,
+}
+
+/// The 8 normal, bright or dim ansi colors.
+#[derive(Default, Serialize, Deserialize)]
+pub struct YmlAnsiColors {
+    pub black: Rgb,
+    pub red: Rgb,
+    pub green: Rgb,
+    pub yellow: Rgb,
+    pub blue: Rgb,
+    pub magenta: Rgb,
+    pub cyan: Rgb,
+    pub white: Rgb,
+}
+
+impl YmlAnsiColors {
+    /// The colors in ansi order.
+    pub fn entries(&self) -> [(&'static str, Rgb); 8] {
+        [
+            ("black", self.black),
+            ("red", self.red),
+            ("green", self.green),
+            ("yellow", self.yellow),
+            ("blue", self.blue),
+            ("magenta", self.magenta),
+            ("cyan", self.cyan),
+            ("white", self.white),
+        ]
+    }
+
+    /// The colors in ansi order, to change them in place.
+    pub fn entries_mut(&mut self) -> [(&'static str, &mut Rgb); 8] {
+        [
+            ("black", &mut self.black),
+            ("red", &mut self.red),
+            ("green", &mut self.green),
+            ("yellow", &mut self.yellow),
+            ("blue", &mut self.blue),
+            ("magenta", &mut self.magenta),
+            ("cyan", &mut self.cyan),
+            ("white", &mut self.white),
+        ]
+    }
+}
+
+/// A color of the 256 color palette, `index` is 16 or higher.
+#[derive(Serialize, Deserialize)]
+pub struct YmlIndexedColor {
+    pub index: u8,
+    pub color: Rgb,
+}
+
+/// The `colors` section of a theme or config. `name` and `author` are not read by alacritty,
+/// themes use them to describe themselves.
+#[derive(Default, Serialize, Deserialize)]
+pub struct YmlColors {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    pub primary: YmlPrimary,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<YmlCursor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vi_mode_cursor: Option<YmlCursor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search: Option<YmlSearch>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hints: Option<YmlHints>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_indicator: Option<YmlBarColors>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub footer_bar: Option<YmlBarColors>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selection: Option<YmlSelection>,
+    pub normal: YmlAnsiColors,
+    pub bright: YmlAnsiColors,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dim: Option<YmlAnsiColors>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub indexed_colors: Vec<YmlIndexedColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transparent_background_colors: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub draw_bold_text_with_bright_colors: Option<bool>,
+}
+
+/// A theme file, or the part of the alacritty config a theme replaces.
+#[derive(Default, Serialize, Deserialize)]
+pub struct YmlColor {
+    pub colors: YmlColors,
+}
+
+/// Read and parse a yaml or toml theme file.
+pub fn load_theme(path: &Path) -> Result<YmlColor, Error> {
+    let contents = read_file(path)?;
+
+    ConfigFormat::from_path(path).parse(path, &contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_tables_load() {
+        let theme: YmlColor = toml::from_str(
+            r##"
+[colors.primary]
+background = "#000000"
+foreground = "#ffffff"
+
+[colors.cursor]
+cursor = "#ff0000"
+
+[colors.selection]
+background = "CellForeground"
+
+[colors.search.matches]
+background = "#00ff00"
+
+[colors.line_indicator]
+foreground = "None"
+background = "#222222"
+
+[colors.normal]
+black = "#000000"
+red = "#ff0000"
+green = "#00ff00"
+yellow = "#ffff00"
+blue = "#0000ff"
+magenta = "#ff00ff"
+cyan = "#00ffff"
+white = "#ffffff"
+
+[colors.bright]
+black = "#000000"
+red = "#ff0000"
+green = "#00ff00"
+yellow = "#ffff00"
+blue = "#0000ff"
+magenta = "#ff00ff"
+cyan = "#00ffff"
+white = "#ffffff"
+"##,
+        )
+        .unwrap();
+        let colors = theme.colors;
+
+        let cursor = colors.cursor.unwrap();
+        assert_eq!(cursor.text, None);
+        assert_eq!(cursor.cursor, Some("#ff0000".parse().unwrap()));
+
+        let selection = colors.selection.unwrap();
+        assert_eq!(selection.text, None);
+        assert_eq!(selection.background, Some(CellRgb::CellForeground));
+
+        let matches = colors.search.unwrap().matches.unwrap();
+        assert_eq!(matches.foreground, None);
+
+        let line_indicator = colors.line_indicator.unwrap();
+        assert_eq!(line_indicator.foreground, None);
+        assert_eq!(line_indicator.background, Some("#222222".parse().unwrap()));
+    }
+}
@@ -0,0 +1,1157 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use std::{io, io::stdout};
+
+use alacritty_theme_switcher::color::{CellRgb, Rgb};
+use alacritty_theme_switcher::config::{apply_theme, backup_cfg_file, ApplyMode, ConfigSnapshot};
+use alacritty_theme_switcher::contrast::ContrastReport;
+use alacritty_theme_switcher::cvd::Deficiency;
+use alacritty_theme_switcher::error::Error;
+use alacritty_theme_switcher::ipc::Ipc;
+use alacritty_theme_switcher::settings::Settings;
+use alacritty_theme_switcher::store::ThemeStore;
+use alacritty_theme_switcher::theme::{
+    load_theme, YmlBarColors, YmlColor, YmlColors, YmlForegroundBackground,
+};
+use color_eyre::config::HookBuilder;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind},
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    ExecutableCommand,
+};
+use ratatui::{prelude::*, style::palette::tailwind, widgets::*};
+
+use crate::color_mode::ColorMode;
+use crate::fuzzy::fuzzy_match;
+use crate::sample::{sample_lines, SAMPLE_HEIGHT};
+
+const TODO_HEADER_BG: Color = tailwind::BLUE.c950;
+const NORMAL_ROW_COLOR: Color = tailwind::SLATE.c950;
+const SELECTED_STYLE_FG: Color = tailwind::BLUE.c300;
+const TEXT_COLOR: Color = tailwind::SLATE.c200;
+const SEARCH_MATCH_FG: Color = tailwind::AMBER.c400;
+
+// the width of a color swatch in the info pane, fits ` #rrggbb `
+const SWATCH_WIDTH: usize = 9;
+
+// how long the selection has to stay on a theme before it is previewed
+const PREVIEW_DELAY: Duration = Duration::from_millis(150);
+
+struct ListItem {
+    name: String,
+    path: PathBuf,
+    // the subdir of the theme dir the file is in
+    group: PathBuf,
+    // the theme source the file is from
+    source: String,
+    // the `name` and `author` of the theme, searched together with the file name
+    theme_name: Option<String>,
+    author: Option<String>,
+}
+
+// the part of a theme the search matched
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MatchField {
+    Name,
+    ThemeName,
+    Author,
+}
+
+// the matched characters of a search result, for highlighting
+struct SearchMatch {
+    field: MatchField,
+    positions: Vec<usize>,
+}
+
+// a line of the themes list
+enum Row {
+    // a folder, `len` is the number of themes in it and its subfolders
+    Group {
+        path: PathBuf,
+        depth: usize,
+        len: usize,
+    },
+    // `index` is the position of the theme in `StatefulList::items`, `matched` is set while
+    // searching
+    Theme {
+        index: usize,
+        depth: usize,
+        matched: Option<SearchMatch>,
+    },
+}
+
+struct StatefulList {
+    state: ListState,
+    // `None` in window only mode, the config is not even looked up
+    alacritty_cfg_file: Option<PathBuf>,
+    items: Vec<ListItem>,
+    // the visible rows, rebuilt when a folder is opened or closed
+    rows: Vec<Row>,
+    collapsed: HashSet<PathBuf>,
+    // the search, the rows are the matching themes when it is not empty
+    query: String,
+    last_selected: Option<usize>,
+}
+
+pub struct App {
+    items: StatefulList,
+    apply_mode: ApplyMode,
+    // write the selected theme to the config while browsing
+    preview: bool,
+    // the config as it was at startup or when a theme was last applied, restored on quit. `None`
+    // in window only mode
+    snapshot: Option<ConfigSnapshot>,
+    // recolor the window over alacritty's IPC socket while previewing
+    ipc: Option<Ipc>,
+    // themes are only applied to the window over IPC, the config is never written
+    window_only: bool,
+    // the theme applied to the window in `window_only` mode
+    window_theme: Option<PathBuf>,
+    // the theme shown by the last preview
+    previewed: Option<PathBuf>,
+    // the config or the IPC options were changed by a preview
+    config_previewed: bool,
+    ipc_previewed: bool,
+    // when the selection last changed, the preview waits until it settles
+    selection_changed: Option<Instant>,
+    // keys are typed into the search
+    searching: bool,
+    // the result of the last action, shown in the footer
+    status: Option<Result<String, String>>,
+    // the colors the terminal can show, every frame is quantized to it
+    color_mode: ColorMode,
+    // the sample is drawn as seen with this color vision deficiency
+    deficiency: Option<Deficiency>,
+}
+
+fn get_themes(store: &ThemeStore) -> Result<Vec<ListItem>, Error> {
+    let themes = store.themes()?;
+
+    let mut themes: Vec<ListItem> = themes
+        .into_iter()
+        .map(|theme| {
+            // a theme that fails to load is still listed, the info pane shows the error
+            let colors = load_theme(&theme.path).ok().map(|theme| theme.colors);
+
+            ListItem {
+                name: theme.name,
+                path: theme.path,
+                group: theme.group,
+                source: theme.source,
+                theme_name: colors.as_ref().and_then(|colors| colors.name.clone()),
+                author: colors.and_then(|colors| colors.author),
+            }
+        })
+        .collect();
+    // the sources are merged into one list, the themes of a folder are kept together
+    themes.sort_by_key(|theme| (theme.group.clone(), theme.name.to_lowercase()));
+
+    Ok(themes)
+}
+
+pub fn init_error_hooks() -> color_eyre::Result<()> {
+    let (panic, error) = HookBuilder::default().into_hooks();
+    let panic = panic.into_panic_hook();
+    let error = error.into_eyre_hook();
+    color_eyre::eyre::set_hook(Box::new(move |e| {
+        let _ = restore_terminal();
+        error(e)
+    }))?;
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = restore_terminal();
+        panic(info);
+    }));
+    Ok(())
+}
+
+pub fn init_terminal() -> color_eyre::Result<Terminal<impl Backend>> {
+    enable_raw_mode()?;
+    stdout().execute(EnterAlternateScreen)?;
+    let backend = CrosstermBackend::new(stdout());
+    let terminal = Terminal::new(backend)?;
+    Ok(terminal)
+}
+
+pub fn restore_terminal() -> color_eyre::Result<()> {
+    disable_raw_mode()?;
+    stdout().execute(LeaveAlternateScreen)?;
+    Ok(())
+}
+
+fn render_title(area: Rect, buf: &mut Buffer) {
+    Paragraph::new("Themes switcher")
+        .bold()
+        .centered()
+        .render(area, buf);
+}
+
+// the sample as seen with `deficiency`, when it is set
+fn render_sample(
+    area: Rect,
+    buf: &mut Buffer,
+    theme: Option<&Result<YmlColor, Error>>,
+    deficiency: Option<Deficiency>,
+) {
+    let title = match deficiency {
+        Some(deficiency) => format!("Sample ({deficiency})"),
+        None => "Sample".to_string(),
+    };
+    let outer_block = Block::default()
+        .borders(Borders::NONE)
+        .fg(TEXT_COLOR)
+        .bg(TODO_HEADER_BG)
+        .title(title)
+        .title_alignment(Alignment::Center);
+    let inner_area = outer_block.inner(area);
+
+    outer_block.render(area, buf);
+
+    // the info pane shows why a theme failed to load
+    let Some(Ok(theme)) = theme else {
+        Block::default()
+            .bg(NORMAL_ROW_COLOR)
+            .render(inner_area, buf);
+        return;
+    };
+
+    let primary = &theme.colors.primary;
+
+    Paragraph::new(sample_lines(&theme.colors))
+        .style(
+            Style::new()
+                .bg(tui_color(primary.background))
+                .fg(tui_color(primary.foreground)),
+        )
+        .block(Block::default().padding(Padding::horizontal(1)))
+        .render(inner_area, buf);
+
+    let Some(deficiency) = deficiency else {
+        return;
+    };
+
+    let simulate = |color: Color| match color {
+        Color::Rgb(r, g, b) => tui_color(deficiency.simulate(Rgb { r, g, b })),
+        color => color,
+    };
+
+    for y in inner_area.top()..inner_area.bottom() {
+        for x in inner_area.left()..inner_area.right() {
+            let cell = buf.get_mut(x, y);
+            cell.fg = simulate(cell.fg);
+            cell.bg = simulate(cell.bg);
+        }
+    }
+}
+
+fn render_footer(
+    area: Rect,
+    buf: &mut Buffer,
+    status: Option<&Result<String, String>>,
+    search: Option<&str>,
+) {
+    let status = match (search, status) {
+        (Some(query), _) => Line::from(vec![
+            Span::styled("/", Style::new().bold()),
+            Span::raw(query.to_string()),
+            Span::styled(" ", Style::new().reversed()),
+        ]),
+        (None, Some(Ok(message))) => Line::from(message.as_str()),
+        (None, Some(Err(message))) => {
+            Line::styled(message.as_str(), Style::new().bold().fg(Color::Red))
+        }
+        (None, None) => Line::default(),
+    };
+
+    let help = if search.is_some() {
+        "Type to search, ↓↑ to move, enter to keep the results, esc to clear the search."
+    } else {
+        "Use ↓↑ to move, a to apply theme, enter to open/close a folder, / to search, g/G to go top/bottom, c for color blindness."
+    };
+
+    Paragraph::new(vec![status, Line::from(help)])
+        .centered()
+        .render(area, buf);
+}
+
+impl StatefulList {
+    fn with_items(items: Vec<ListItem>, alacritty_cfg_file: Option<PathBuf>) -> StatefulList {
+        let mut list = StatefulList {
+            state: ListState::default(),
+            items,
+            alacritty_cfg_file,
+            rows: Vec::new(),
+            collapsed: HashSet::new(),
+            query: String::new(),
+            last_selected: None,
+        };
+        list.update_rows();
+        list
+    }
+
+    // the themes matching the search, best match first
+    fn search_rows(&self) -> Vec<Row> {
+        let mut results: Vec<(i64, Row)> = Vec::new();
+
+        for (index, item) in self.items.iter().enumerate() {
+            let fields = [
+                (MatchField::Name, Some(&item.name)),
+                (MatchField::ThemeName, item.theme_name.as_ref()),
+                (MatchField::Author, item.author.as_ref()),
+            ];
+
+            let best = fields
+                .into_iter()
+                .filter_map(|(field, text)| {
+                    let (score, positions) = fuzzy_match(&self.query, text?)?;
+                    Some((score, SearchMatch { field, positions }))
+                })
+                .max_by_key(|(score, _)| *score);
+
+            if let Some((score, matched)) = best {
+                results.push((
+                    score,
+                    Row::Theme {
+                        index,
+                        depth: 0,
+                        matched: Some(matched),
+                    },
+                ));
+            }
+        }
+
+        // the sort is stable, so equal scores keep the order of the list
+        results.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+
+        results.into_iter().map(|(_, row)| row).collect()
+    }
+
+    // lay out the folders and the themes that are not in a closed folder
+    fn update_rows(&mut self) {
+        if !self.query.is_empty() {
+            self.rows = self.search_rows();
+            return;
+        }
+
+        self.rows.clear();
+
+        let mut previous: Vec<&std::ffi::OsStr> = Vec::new();
+
+        for (index, item) in self.items.iter().enumerate() {
+            let components: Vec<&std::ffi::OsStr> = item.group.iter().collect();
+
+            // a header for every folder that was not open for the previous theme
+            let common = previous
+                .iter()
+                .zip(&components)
+                .take_while(|(a, b)| a == b)
+                .count();
+
+            for depth in common..components.len() {
+                let path: PathBuf = components[..=depth].iter().collect();
+                let hidden = self
+                    .collapsed
+                    .iter()
+                    .any(|closed| path.starts_with(closed) && path != *closed);
+
+                if !hidden {
+                    let len = self
+                        .items
+                        .iter()
+                        .filter(|item| item.group.starts_with(&path))
+                        .count();
+
+                    self.rows.push(Row::Group { path, depth, len });
+                }
+            }
+
+            if !self
+                .collapsed
+                .iter()
+                .any(|closed| item.group.starts_with(closed))
+            {
+                self.rows.push(Row::Theme {
+                    index,
+                    depth: components.len(),
+                    matched: None,
+                });
+            }
+
+            previous = components;
+        }
+    }
+
+    // the theme shown in the info pane, the first row is shown when nothing is selected
+    fn selected_row_theme(&self) -> Option<&ListItem> {
+        match self.rows.get(self.state.selected().unwrap_or(0))? {
+            Row::Theme { index, .. } => self.items.get(*index),
+            Row::Group { .. } => None,
+        }
+    }
+
+    // the theme of the selected row, if a theme is selected
+    fn selected_theme(&self) -> Option<&ListItem> {
+        match self.rows.get(self.state.selected()?)? {
+            Row::Theme { index, .. } => self.items.get(*index),
+            Row::Group { .. } => None,
+        }
+    }
+
+    // change the search and select the best match
+    fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.update_rows();
+        self.state
+            .select(if self.rows.is_empty() { None } else { Some(0) });
+    }
+
+    // open or close the selected folder
+    fn toggle_group(&mut self) {
+        let Some(Row::Group { path, .. }) = self.state.selected().and_then(|i| self.rows.get(i))
+        else {
+            return;
+        };
+
+        if !self.collapsed.remove(path) {
+            self.collapsed.insert(path.clone());
+        }
+
+        // the rows before the folder don't change, so the selection stays on it
+        self.update_rows();
+    }
+
+    fn next(&mut self) {
+        if self.rows.is_empty() {
+            return;
+        }
+
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i >= self.rows.len() - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => self.last_selected.unwrap_or(0),
+        };
+        self.state.select(Some(i));
+    }
+
+    fn previous(&mut self) {
+        if self.rows.is_empty() {
+            return;
+        }
+
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i == 0 {
+                    self.rows.len() - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => self.last_selected.unwrap_or(0),
+        };
+        self.state.select(Some(i));
+    }
+}
+
+// `text` with the characters at `positions` highlighted
+fn highlight_spans(text: &str, positions: &[usize], style: Style) -> Vec<Span<'static>> {
+    text.chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if positions.contains(&i) {
+                Span::styled(c.to_string(), style.fg(SEARCH_MATCH_FG).bold())
+            } else {
+                Span::styled(c.to_string(), style)
+            }
+        })
+        .collect()
+}
+
+// `name:` followed by a swatch of the color
+fn color_line(name: &str, color: Option<CellRgb>) -> Line<'static> {
+    Line::from(vec![Span::raw(format!("{name}: ")), color_span(color)])
+}
+
+// `name:` followed by the foreground and background swatches
+fn pair_line(name: &str, pair: Option<&YmlForegroundBackground>) -> Line<'static> {
+    Line::from(vec![
+        Span::raw(format!("{name}: ")),
+        color_span(pair.and_then(|pair| pair.foreground)),
+        Span::raw(" "),
+        color_span(pair.and_then(|pair| pair.background)),
+    ])
+}
+
+// `name:` followed by the foreground and background swatches of a bar
+fn bar_line(name: &str, bar: Option<&YmlBarColors>) -> Line<'static> {
+    let swatch = |color: Option<Rgb>| color_span(color.map(CellRgb::Rgb));
+
+    Line::from(vec![
+        Span::raw(format!("{name}: ")),
+        swatch(bar.and_then(|bar| bar.foreground)),
+        Span::raw(" "),
+        swatch(bar.and_then(|bar| bar.background)),
+    ])
+}
+
+// ratatui's `Color` can't implement `From<Rgb>` outside of the library
+pub(crate) fn tui_color(rgb: Rgb) -> Color {
+    Color::Rgb(rgb.r, rgb.g, rgb.b)
+}
+
+// a block of the color with its hex code in black or white, whichever is readable on it
+fn color_span(color: Option<CellRgb>) -> Span<'static> {
+    match color {
+        Some(CellRgb::Rgb(rgb)) => Span::styled(
+            format!(" {:<width$} ", rgb.to_string(), width = SWATCH_WIDTH - 2),
+            Style::new()
+                .bg(tui_color(rgb))
+                .fg(tui_color(rgb.contrasting_label())),
+        ),
+        // the cell colors depend on the text under the cursor, there is nothing to show
+        Some(color) => Span::styled(
+            format!("{:<SWATCH_WIDTH$}", color.to_string()),
+            Style::new().italic(),
+        ),
+        None => Span::styled(format!("{:<SWATCH_WIDTH$}", "Empty"), Style::new().bold()),
+    }
+}
+
+// the grade of the theme and its least readable colors
+fn contrast_line(report: &ContrastReport) -> Line<'static> {
+    let mut spans = vec![
+        Span::raw("contrast:"),
+        Span::styled(report.grade().to_string(), Style::new().bold()),
+    ];
+
+    if let Some(lowest) = report.lowest() {
+        spans.push(Span::raw(format!(
+            " (lowest: {} {:.2}:1)",
+            lowest.name, lowest.ratio
+        )));
+    }
+
+    Line::from(spans)
+}
+
+// the lines of the info pane for a parsed theme
+fn theme_info_lines(colors: &YmlColors) -> Vec<Line<'static>> {
+    let mut info: Vec<Line> = vec![
+        Line::from(vec![
+            Span::raw("name:"),
+            Span::styled(
+                colors.name.clone().unwrap_or("Empty".to_string()),
+                Style::new().bold(),
+            ),
+        ]),
+        Line::from(vec![
+            Span::raw("author:"),
+            Span::styled(
+                colors.author.clone().unwrap_or("Empty".to_string()),
+                Style::new().bold(),
+            ),
+        ]),
+        contrast_line(&ContrastReport::new(colors)),
+        // ---
+        Line::from("primary:"),
+        color_line("background", Some(colors.primary.background.into())),
+        color_line("foreground", Some(colors.primary.foreground.into())),
+    ];
+
+    if let Some(dim_foreground) = &colors.primary.dim_foreground {
+        info.push(color_line("dim_foreground", Some((*dim_foreground).into())));
+    }
+    if let Some(bright_foreground) = &colors.primary.bright_foreground {
+        info.push(color_line(
+            "bright_foreground",
+            Some((*bright_foreground).into()),
+        ));
+    }
+
+    // ---
+    info.push(Line::from("cursor:"));
+    info.push(color_line(
+        "text",
+        colors.cursor.as_ref().and_then(|c| c.text),
+    ));
+    info.push(color_line(
+        "cursor",
+        colors.cursor.as_ref().and_then(|c| c.cursor),
+    ));
+
+    if let Some(vi_mode_cursor) = &colors.vi_mode_cursor {
+        info.push(Line::from("vi_mode_cursor:"));
+        info.push(color_line("text", vi_mode_cursor.text));
+        info.push(color_line("cursor", vi_mode_cursor.cursor));
+    }
+
+    if let Some(selection) = &colors.selection {
+        info.push(Line::from("selection:"));
+        info.push(color_line("text", selection.text));
+        info.push(color_line("background", selection.background));
+    }
+
+    if let Some(search) = &colors.search {
+        info.push(Line::from("search:"));
+        info.push(pair_line("matches", search.matches.as_ref()));
+        info.push(pair_line("focused_match", search.focused_match.as_ref()));
+    }
+
+    if let Some(hints) = &colors.hints {
+        info.push(Line::from("hints:"));
+        info.push(pair_line("start", hints.start.as_ref()));
+        info.push(pair_line("end", hints.end.as_ref()));
+    }
+
+    if colors.line_indicator.is_some() || colors.footer_bar.is_some() {
+        info.push(bar_line("line_indicator", colors.line_indicator.as_ref()));
+        info.push(bar_line("footer_bar", colors.footer_bar.as_ref()));
+    }
+
+    // ---
+    info.push(Line::from("normal:"));
+    for (name, color) in colors.normal.entries() {
+        info.push(color_line(name, Some(color.into())));
+    }
+
+    // ---
+    info.push(Line::from("bright:"));
+    for (name, color) in colors.bright.entries() {
+        info.push(color_line(name, Some(color.into())));
+    }
+
+    // ---
+    if let Some(dim) = &colors.dim {
+        info.push(Line::from("dim:"));
+        for (name, color) in dim.entries() {
+            info.push(color_line(name, Some(color.into())));
+        }
+    }
+
+    // ---
+    if !colors.indexed_colors.is_empty() {
+        info.push(Line::from("indexed_colors:"));
+        for indexed_color in &colors.indexed_colors {
+            info.push(color_line(
+                &indexed_color.index.to_string(),
+                Some(indexed_color.color.into()),
+            ));
+        }
+    }
+
+    // ---
+    if let Some(transparent) = colors.transparent_background_colors {
+        info.push(Line::from(format!(
+            "transparent_background_colors:{transparent}"
+        )));
+    }
+    if let Some(draw_bold) = colors.draw_bold_text_with_bright_colors {
+        info.push(Line::from(format!(
+            "draw_bold_text_with_bright_colors:{draw_bold}"
+        )));
+    }
+    info
+}
+
+// the sources that were skipped because their dir is missing
+fn unavailable_sources_status(store: &ThemeStore) -> Option<Result<String, String>> {
+    let names: Vec<&str> = store
+        .unavailable_sources()
+        .map(|source| source.name.as_str())
+        .collect();
+
+    (!names.is_empty()).then(|| {
+        Err(format!(
+            "Skipped the missing theme sources: {}",
+            names.join(", ")
+        ))
+    })
+}
+
+impl App {
+    pub fn new(
+        apply_mode: ApplyMode,
+        settings: &Settings,
+        preview: bool,
+        ipc: Option<Ipc>,
+        window_only: bool,
+        color_mode: ColorMode,
+    ) -> Result<Self, Error> {
+        let store = settings.theme_store()?;
+        let themes = get_themes(&store)?;
+
+        // the config is never written in window only mode, so it doesn't have to exist
+        let (alacritty_cfg_file, snapshot) = if window_only {
+            (None, None)
+        } else {
+            let alacritty_cfg_file = settings.config_file()?;
+            // the backup is made before the first preview, so it is always the original config
+            backup_cfg_file(&alacritty_cfg_file)?;
+            let snapshot = ConfigSnapshot::take(&alacritty_cfg_file, apply_mode)?;
+
+            (Some(alacritty_cfg_file), Some(snapshot))
+        };
+
+        Ok(Self {
+            items: StatefulList::with_items(themes, alacritty_cfg_file),
+            apply_mode,
+            preview,
+            snapshot,
+            ipc,
+            window_only,
+            window_theme: None,
+            previewed: None,
+            config_previewed: false,
+            ipc_previewed: false,
+            selection_changed: None,
+            searching: false,
+            status: unavailable_sources_status(&store),
+            color_mode: color_mode.resolve(),
+            deficiency: None,
+        })
+    }
+
+    // cycle the sample through the deficiencies and back to normal vision
+    fn next_deficiency(&mut self) {
+        self.deficiency = match self.deficiency {
+            None => Some(Deficiency::ALL[0]),
+            Some(deficiency) => Deficiency::ALL
+                .into_iter()
+                .skip_while(|other| *other != deficiency)
+                .nth(1),
+        };
+    }
+
+    fn go_top(&mut self) {
+        if !self.items.rows.is_empty() {
+            self.items.state.select(Some(0));
+        }
+    }
+
+    fn go_bottom(&mut self) {
+        if !self.items.rows.is_empty() {
+            self.items.state.select(Some(self.items.rows.len() - 1));
+        }
+    }
+
+    // apply the selected theme, it is kept when the app is closed
+    fn apply_theme(&mut self) {
+        let Some(theme) = self.items.selected_theme() else {
+            return;
+        };
+
+        if self.window_only {
+            let Some(ipc) = &self.ipc else {
+                return;
+            };
+
+            self.status = Some(match ipc.apply_theme(&theme.path) {
+                Ok(()) => {
+                    self.window_theme = Some(theme.path.clone());
+                    self.previewed = Some(theme.path.clone());
+                    self.ipc_previewed = false;
+                    Ok(format!("Applied {} to this window", theme.name))
+                }
+                Err(e) => Err(e.to_string()),
+            });
+            return;
+        }
+
+        let Some(alacritty_cfg_file) = &self.items.alacritty_cfg_file else {
+            return;
+        };
+
+        let result =
+            apply_theme(alacritty_cfg_file, &theme.path, self.apply_mode).and_then(|applied| {
+                ConfigSnapshot::take(alacritty_cfg_file, self.apply_mode)
+                    .map(|snapshot| (applied, snapshot))
+            });
+
+        self.status = Some(match result {
+            Ok((applied, snapshot)) => {
+                self.snapshot = Some(snapshot);
+                self.config_previewed = false;
+                self.previewed = Some(theme.path.clone());
+                if applied.rewritten {
+                    Ok(format!(
+                        "Applied {}, the config was rewritten and lost its comments",
+                        theme.name
+                    ))
+                } else {
+                    Ok(format!("Applied {}", theme.name))
+                }
+            }
+            Err(e) => Err(e.to_string()),
+        });
+
+        // the window follows the config again, which now has the theme
+        if self.ipc_previewed {
+            if let Some(ipc) = &self.ipc {
+                let _ = ipc.reset();
+            }
+            self.ipc_previewed = false;
+        }
+    }
+
+    // show the selected theme without making it stick, over IPC when possible and otherwise by
+    // writing the config
+    fn preview_theme(&mut self) {
+        let Some(theme) = self.items.selected_theme() else {
+            return;
+        };
+
+        if self.previewed.as_ref() == Some(&theme.path) {
+            return;
+        }
+        self.previewed = Some(theme.path.clone());
+
+        let mut ipc_error = None;
+        if let Some(ipc) = &self.ipc {
+            match ipc.apply_theme(&theme.path) {
+                Ok(()) => {
+                    self.ipc_previewed = true;
+                    self.status = Some(Ok(format!("Previewing {}", theme.name)));
+                    return;
+                }
+                // the config must not be touched in window mode
+                Err(e) if self.window_only => {
+                    self.status = Some(Err(e.to_string()));
+                    return;
+                }
+                // alacritty is gone or the socket is not usable, use the config from now on
+                Err(e) => ipc_error = Some(e),
+            }
+        }
+        if ipc_error.is_some() {
+            self.ipc = None;
+        }
+
+        let Some(alacritty_cfg_file) = &self.items.alacritty_cfg_file else {
+            return;
+        };
+
+        let result = apply_theme(alacritty_cfg_file, &theme.path, self.apply_mode);
+        self.config_previewed = true;
+
+        self.status = Some(match (result, ipc_error) {
+            (Err(e), _) => Err(e.to_string()),
+            (Ok(_), Some(e)) => Err(format!("{e}, previewing through the config instead")),
+            (Ok(_), None) => Ok(format!("Previewing {}", theme.name)),
+        });
+    }
+
+    // undo the previews
+    fn revert(&mut self) -> Result<(), Error> {
+        if let Some(snapshot) = self.snapshot.as_ref().filter(|_| self.config_previewed) {
+            snapshot.restore()?;
+        }
+        if self.ipc_previewed {
+            if let Some(ipc) = &self.ipc {
+                match &self.window_theme {
+                    Some(theme) => ipc.apply_theme(theme)?,
+                    None => ipc.reset()?,
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl App {
+    pub fn run(&mut self, mut terminal: Terminal<impl Backend>) -> io::Result<()> {
+        loop {
+            self.draw(&mut terminal)?;
+
+            // wait for a key, or until the selection settled and the theme can be previewed
+            let timeout = match self.selection_changed {
+                Some(changed) => PREVIEW_DELAY.saturating_sub(changed.elapsed()),
+                None => Duration::from_secs(60),
+            };
+
+            if !event::poll(timeout)? {
+                if self.selection_changed.take().is_some() {
+                    self.preview_theme();
+                }
+                continue;
+            }
+
+            let selected = self.items.selected_theme().map(|theme| theme.path.clone());
+
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    use KeyCode::*;
+
+                    if self.searching {
+                        match key.code {
+                            Esc => {
+                                self.searching = false;
+                                self.items.set_query(String::new());
+                            }
+                            Enter => self.searching = false,
+                            Down => self.items.next(),
+                            Up => self.items.previous(),
+                            Backspace => {
+                                let mut query = self.items.query.clone();
+                                query.pop();
+                                self.items.set_query(query);
+                            }
+                            Char(c) => {
+                                let query = format!("{}{c}", self.items.query);
+                                self.items.set_query(query);
+                            }
+                            _ => {}
+                        }
+                    } else {
+                        match key.code {
+                            // the first esc clears the search results
+                            Esc if !self.items.query.is_empty() => {
+                                self.items.set_query(String::new())
+                            }
+                            Char('q') | Esc => {
+                                return self.revert().map_err(io::Error::other);
+                            }
+                            Char('/') => self.searching = true,
+                            Char('j') | Down => self.items.next(),
+                            Char('k') | Up => self.items.previous(),
+                            Char('g') => self.go_top(),
+                            Char('G') => self.go_bottom(),
+                            Char('a') => self.apply_theme(),
+                            Char('c') => self.next_deficiency(),
+                            // enter keeps the selected theme and closes the app
+                            Enter if self.items.selected_theme().is_some() => {
+                                self.apply_theme();
+                                if let Some(Ok(_)) = self.status {
+                                    return Ok(());
+                                }
+                            }
+                            Enter | Char(' ') => self.items.toggle_group(),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+
+            if self.preview
+                && self.items.selected_theme().map(|theme| &theme.path) != selected.as_ref()
+            {
+                self.selection_changed = Some(Instant::now());
+            }
+        }
+    }
+
+    fn draw(&mut self, terminal: &mut Terminal<impl Backend>) -> io::Result<()> {
+        terminal.draw(|f| f.render_widget(self, f.size()))?;
+        Ok(())
+    }
+}
+
+impl Widget for &mut App {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // Create a space for header, todo list and the footer.
+        let vertical = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Min(0),
+            Constraint::Length(2),
+        ]);
+        let [header_area, rest_area, footer_area] = vertical.areas(area);
+
+        // Create two chunks with equal vertical screen space. One for the list and the other for
+        // the info block.
+        let horizontal =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]);
+        let [upper_item_list_area, lower_item_list_area] = horizontal.areas(rest_area);
+
+        // The sample terminal goes above the info.
+        let [sample_area, info_area] =
+            Layout::vertical([Constraint::Length(SAMPLE_HEIGHT + 1), Constraint::Min(0)])
+                .areas(lower_item_list_area);
+
+        // get theme file and parse it
+        let theme = self
+            .items
+            .selected_row_theme()
+            .map(|theme| load_theme(&theme.path));
+
+        render_title(header_area, buf);
+        self.render_todo(upper_item_list_area, buf);
+        render_sample(sample_area, buf, theme.as_ref(), self.deficiency);
+        self.render_info(info_area, buf, theme.as_ref());
+        render_footer(
+            footer_area,
+            buf,
+            self.status.as_ref(),
+            self.searching.then_some(self.items.query.as_str()),
+        );
+
+        self.color_mode.quantize_buffer(area, buf);
+    }
+}
+
+impl App {
+    fn render_todo(&mut self, area: Rect, buf: &mut Buffer) {
+        // We create two blocks, one is for the header (outer) and the other is for list (inner).
+        let outer_block = Block::default()
+            .borders(Borders::NONE)
+            .fg(TEXT_COLOR)
+            .bg(TODO_HEADER_BG)
+            .title("Themes List")
+            .title_alignment(Alignment::Center);
+        let inner_block = Block::default()
+            .borders(Borders::NONE)
+            .fg(TEXT_COLOR)
+            .bg(NORMAL_ROW_COLOR);
+
+        // We get the inner area from outer_block. We'll use this area later to render the table.
+        let outer_area = area;
+        let inner_area = outer_block.inner(outer_area);
+
+        // We can render the header in outer_area.
+        outer_block.render(outer_area, buf);
+
+        // the source is only worth showing when there is more than one
+        let show_source = self
+            .items
+            .items
+            .iter()
+            .any(|item| item.source != self.items.items[0].source);
+
+        // Iterate through all rows and stylize them.
+        let items: Vec<Line> = self
+            .items
+            .rows
+            .iter()
+            .map(|row| match row {
+                Row::Group { path, depth, len } => {
+                    let marker = if self.items.collapsed.contains(path) {
+                        "▸"
+                    } else {
+                        "▾"
+                    };
+                    let name = path.file_name().unwrap_or_default().to_string_lossy();
+
+                    Line::styled(
+                        format!("{}{marker} {name}/ ({len})", "  ".repeat(*depth)),
+                        Style::new().fg(TEXT_COLOR).bold(),
+                    )
+                }
+                Row::Theme {
+                    index,
+                    depth,
+                    matched,
+                } => {
+                    let item = &self.items.items[*index];
+                    let positions = |field| {
+                        matched
+                            .as_ref()
+                            .filter(|matched| matched.field == field)
+                            .map_or(&[][..], |matched| &matched.positions[..])
+                    };
+
+                    let mut line = Line::from(Span::raw("  ".repeat(*depth)));
+                    line.spans.extend(highlight_spans(
+                        &item.name,
+                        positions(MatchField::Name),
+                        Style::new().fg(TEXT_COLOR),
+                    ));
+
+                    // show the metadata that matched the search
+                    let metadata = match matched.as_ref().map(|matched| matched.field) {
+                        Some(MatchField::ThemeName) => item.theme_name.as_ref(),
+                        Some(MatchField::Author) => item.author.as_ref(),
+                        _ => None,
+                    };
+                    if let (Some(metadata), Some(matched)) = (metadata, matched) {
+                        line.spans.push(Span::styled(" · ", Style::new().dim()));
+                        line.spans.extend(highlight_spans(
+                            metadata,
+                            &matched.positions,
+                            Style::new().fg(TEXT_COLOR).dim(),
+                        ));
+                    }
+
+                    if show_source {
+                        line.spans.push(Span::styled(
+                            format!(" [{}]", item.source),
+                            Style::new().dim(),
+                        ));
+                    }
+                    line
+                }
+            })
+            .collect();
+
+        // Create a List from all list items and highlight the currently selected one
+        let items = List::new(items)
+            .block(inner_block)
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .add_modifier(Modifier::REVERSED)
+                    .fg(SELECTED_STYLE_FG),
+            )
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always);
+
+        // We can now render the item list
+        // (look careful we are using StatefulWidget's render.)
+        // ratatui::widgets::StatefulWidget::render as stateful_render
+        StatefulWidget::render(items, inner_area, buf, &mut self.items.state);
+    }
+
+    fn render_info(
+        &self,
+        area: Rect,
+        buf: &mut Buffer,
+        theme_colors: Option<&Result<YmlColor, Error>>,
+    ) {
+        let row_index = self.items.state.selected().unwrap_or(0);
+
+        let info: Vec<Line> = match (theme_colors, self.items.rows.get(row_index)) {
+            (Some(Ok(theme_colors)), _) => theme_info_lines(&theme_colors.colors),
+            (Some(Err(e)), _) => vec![
+                Line::styled("Failed to load theme:", Style::new().bold().fg(Color::Red)),
+                Line::from(e.to_string()),
+            ],
+            (None, Some(Row::Group { path, len, .. })) => vec![
+                Line::from(vec![
+                    Span::raw("folder:"),
+                    Span::styled(path.display().to_string(), Style::new().bold()),
+                ]),
+                Line::from(format!("themes:{len}")),
+            ],
+            (None, _) => vec![Line::from("No themes found")],
+        };
+
+        // We show the list item's info under the list in this paragraph
+        let outer_info_block = Block::default()
+            .borders(Borders::NONE)
+            .fg(TEXT_COLOR)
+            .bg(TODO_HEADER_BG)
+            .title("Info")
+            .title_alignment(Alignment::Center);
+        let inner_info_block = Block::default()
+            .borders(Borders::NONE)
+            .bg(NORMAL_ROW_COLOR)
+            .padding(Padding::horizontal(1));
+
+        // This is a similar process to what we did for list. outer_info_area will be used for
+        // header inner_info_area will be used for the list info.
+        let outer_info_area = area;
+        let inner_info_area = outer_info_block.inner(outer_info_area);
+
+        // We can render the header. Inner info will be rendered later
+        outer_info_block.render(outer_info_area, buf);
+
+        let info_paragraph = Paragraph::new(info)
+            .block(inner_info_block)
+            .fg(TEXT_COLOR)
+            .wrap(Wrap { trim: false });
+
+        info_paragraph.render(inner_info_area, buf);
+    }
+}
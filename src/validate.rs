@@ -0,0 +1,770 @@
+//! Linting theme files.
+
+use std::path::{Path, PathBuf};
+
+use crate::color::{CellRgb, Rgb};
+use crate::config::ConfigFormat;
+use crate::error::{read_file, Error};
+use crate::store::ThemeStore;
+use crate::theme::YmlColor;
+
+/// A problem found in a theme file, `line` and `column` start at 1.
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path.display())?;
+
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+
+            if let Some(column) = self.column {
+                write!(f, ":{column}")?;
+            }
+        }
+
+        write!(f, ": {}", self.message)
+    }
+}
+
+// line and column of a byte offset
+fn line_column(contents: &str, offset: usize) -> (usize, usize) {
+    let before = &contents[..offset.min(contents.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map(|i| i + 1).unwrap_or(0) + 1;
+
+    (line, column)
+}
+
+/// Find the line a key is defined on, `path` is the full path of the key like
+/// `["colors", "primary"]`.
+pub fn find_key_line(contents: &str, format: ConfigFormat, path: &[&str]) -> Option<usize> {
+    match format {
+        // find each parent key after the previous one, flow mappings can have several keys on
+        // the same line
+        ConfigFormat::Yaml => {
+            let is_key = |l: &str, key: &str| {
+                l.match_indices(key).any(|(i, _)| {
+                    let before = l[..i].trim_end();
+                    let after = l[i + key.len()..].trim_start();
+
+                    (before.is_empty() || before.ends_with(['{', ',', '-']))
+                        && after.starts_with(':')
+                })
+            };
+            let mut line = 0;
+
+            for key in path {
+                line += contents.lines().skip(line).position(|l| is_key(l, key))?;
+            }
+
+            Some(line + 1)
+        }
+        // track the current `[table]` and compare the full path of every key
+        ConfigFormat::Toml => {
+            let split = |key: &str| -> Vec<String> {
+                key.split('.')
+                    .map(|k| k.trim().trim_matches(['"', '\'']).to_string())
+                    .collect()
+            };
+            let mut table: Vec<String> = Vec::new();
+
+            for (i, l) in contents.lines().enumerate() {
+                let l = l.trim();
+
+                let full = if let Some(header) = l.strip_prefix('[') {
+                    let header = header.trim_start_matches('[');
+                    table = split(&header[..header.find(']').unwrap_or(header.len())]);
+                    table.clone()
+                } else if let Some((key, _)) = l.split_once('=') {
+                    [table.clone(), split(key)].concat()
+                } else {
+                    continue;
+                };
+
+                if full.len() >= path.len() && full[..path.len()] == *path {
+                    return Some(i + 1);
+                }
+            }
+
+            None
+        }
+    }
+}
+
+// what a key of a theme holds
+enum Kind {
+    Rgb,
+    CellRgb,
+    // a color or `None`, used by the footer bar and line indicator
+    RgbOrNone,
+    Bool,
+    Text,
+    Table(&'static [Field]),
+    IndexedColors,
+}
+
+// a key of a theme, as alacritty reads it
+struct Field {
+    name: &'static str,
+    kind: Kind,
+    required: bool,
+}
+
+const fn field(name: &'static str, kind: Kind) -> Field {
+    Field {
+        name,
+        kind,
+        required: false,
+    }
+}
+
+const fn required(name: &'static str, kind: Kind) -> Field {
+    Field {
+        name,
+        kind,
+        required: true,
+    }
+}
+
+const PRIMARY: &[Field] = &[
+    required("background", Kind::Rgb),
+    required("foreground", Kind::Rgb),
+    field("dim_foreground", Kind::Rgb),
+    field("bright_foreground", Kind::Rgb),
+];
+
+const CURSOR: &[Field] = &[field("text", Kind::CellRgb), field("cursor", Kind::CellRgb)];
+
+const FOREGROUND_BACKGROUND: &[Field] = &[
+    field("foreground", Kind::CellRgb),
+    field("background", Kind::CellRgb),
+];
+
+const BAR: &[Field] = &[
+    field("foreground", Kind::RgbOrNone),
+    field("background", Kind::RgbOrNone),
+];
+
+const SELECTION: &[Field] = &[
+    field("text", Kind::CellRgb),
+    field("background", Kind::CellRgb),
+];
+
+const SEARCH: &[Field] = &[
+    field("matches", Kind::Table(FOREGROUND_BACKGROUND)),
+    field("focused_match", Kind::Table(FOREGROUND_BACKGROUND)),
+];
+
+const HINTS: &[Field] = &[
+    field("start", Kind::Table(FOREGROUND_BACKGROUND)),
+    field("end", Kind::Table(FOREGROUND_BACKGROUND)),
+];
+
+const ANSI: &[Field] = &[
+    required("black", Kind::Rgb),
+    required("red", Kind::Rgb),
+    required("green", Kind::Rgb),
+    required("yellow", Kind::Rgb),
+    required("blue", Kind::Rgb),
+    required("magenta", Kind::Rgb),
+    required("cyan", Kind::Rgb),
+    required("white", Kind::Rgb),
+];
+
+const COLORS: &[Field] = &[
+    field("name", Kind::Text),
+    field("author", Kind::Text),
+    required("primary", Kind::Table(PRIMARY)),
+    field("cursor", Kind::Table(CURSOR)),
+    field("vi_mode_cursor", Kind::Table(CURSOR)),
+    field("search", Kind::Table(SEARCH)),
+    field("hints", Kind::Table(HINTS)),
+    field("line_indicator", Kind::Table(BAR)),
+    field("footer_bar", Kind::Table(BAR)),
+    field("selection", Kind::Table(SELECTION)),
+    required("normal", Kind::Table(ANSI)),
+    required("bright", Kind::Table(ANSI)),
+    field("dim", Kind::Table(ANSI)),
+    field("indexed_colors", Kind::IndexedColors),
+    field("transparent_background_colors", Kind::Bool),
+    field("draw_bold_text_with_bright_colors", Kind::Bool),
+];
+
+const THEME: &[Field] = &[required("colors", Kind::Table(COLORS))];
+
+const INDEXED_COLOR_KEYS: [&str; 2] = ["index", "color"];
+
+// the dotted name of a key in the table called `parent`, which is empty for the top level
+fn child_name(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{parent}.{key}")
+    }
+}
+
+// collects the diagnostics of one theme file
+struct Checker<'a> {
+    path: &'a Path,
+    contents: &'a str,
+    format: ConfigFormat,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    // report a problem on the line of the key at `keys`, or of its closest parent that is found
+    fn report(&mut self, keys: &[&str], message: String) {
+        let line = (1..=keys.len())
+            .rev()
+            .find_map(|len| find_key_line(self.contents, self.format, &keys[..len]));
+
+        self.diagnostics.push(Diagnostic {
+            path: self.path.to_path_buf(),
+            line,
+            column: None,
+            message,
+        });
+    }
+
+    // check every key of a table against `fields`, every problem is reported and not just the
+    // first one
+    fn check_table(
+        &mut self,
+        keys: &[&str],
+        name: &str,
+        value: &serde_yaml::Value,
+        fields: &[Field],
+    ) {
+        let Some(table) = value.as_mapping() else {
+            self.report(keys, format!("`{name}` should be a table"));
+            return;
+        };
+
+        for (key, _) in table {
+            let known = key
+                .as_str()
+                .is_some_and(|key| fields.iter().any(|field| field.name == key));
+
+            if !known {
+                let key = key
+                    .as_str()
+                    .map_or_else(|| format!("{key:?}"), str::to_string);
+                let key_path = [keys, &[key.as_str()]].concat();
+                self.report(
+                    &key_path,
+                    format!("unknown key `{}`", child_name(name, &key)),
+                );
+            }
+        }
+
+        for field in fields {
+            let key_path = [keys, &[field.name]].concat();
+            let name = child_name(name, field.name);
+
+            match table.get(field.name) {
+                Some(value) => self.check_value(&key_path, &name, value, &field.kind),
+                None if field.required => {
+                    self.report(keys, format!("missing key `{name}`"));
+                }
+                None => {}
+            }
+        }
+    }
+
+    fn check_value(&mut self, keys: &[&str], name: &str, value: &serde_yaml::Value, kind: &Kind) {
+        let message = match (kind, value) {
+            (Kind::Table(fields), value) => {
+                self.check_table(keys, name, value, fields);
+                return;
+            }
+            (Kind::IndexedColors, serde_yaml::Value::Sequence(colors)) => {
+                for (i, color) in colors.iter().enumerate() {
+                    self.check_indexed_color(keys, &format!("{name}[{i}]"), color);
+                }
+                return;
+            }
+            (Kind::IndexedColors, _) => format!("`{name}` should be a list"),
+            (Kind::Bool, serde_yaml::Value::Bool(_))
+            | (Kind::Text, serde_yaml::Value::String(_)) => return,
+            (Kind::Bool, _) => format!("`{name}` should be true or false"),
+            (Kind::Text, _) => format!("`{name}` should be a string"),
+            (Kind::RgbOrNone, serde_yaml::Value::String(color)) if color == "None" => return,
+            (Kind::Rgb | Kind::RgbOrNone, serde_yaml::Value::String(color)) => {
+                match color.parse::<Rgb>() {
+                    Ok(_) => return,
+                    Err(e) => format!("`{name}`: {e}"),
+                }
+            }
+            (Kind::CellRgb, serde_yaml::Value::String(color)) => match color.parse::<CellRgb>() {
+                Ok(_) => return,
+                Err(e) => format!("`{name}`: {e}"),
+            },
+            // `0x123456` without quotes is a number in yaml
+            (Kind::Rgb | Kind::RgbOrNone | Kind::CellRgb, _) => {
+                format!("`{name}` should be a color in quotes, like \"#rrggbb\"")
+            }
+        };
+
+        self.report(keys, message);
+    }
+
+    // an entry of `indexed_colors`, the first 16 colors are set by `normal` and `bright`
+    fn check_indexed_color(&mut self, keys: &[&str], name: &str, value: &serde_yaml::Value) {
+        let Some(table) = value.as_mapping() else {
+            self.report(keys, format!("`{name}` should be a table"));
+            return;
+        };
+
+        for (key, _) in table {
+            let key = key
+                .as_str()
+                .map_or_else(|| format!("{key:?}"), str::to_string);
+
+            if !INDEXED_COLOR_KEYS.contains(&key.as_str()) {
+                self.report(keys, format!("unknown key `{name}.{key}`"));
+            }
+        }
+
+        match table.get("index").map(|index| index.as_u64()) {
+            Some(Some(16..=255)) => {}
+            Some(_) => self.report(
+                keys,
+                format!("`{name}.index` should be a number from 16 to 255"),
+            ),
+            None => self.report(keys, format!("missing key `{name}.index`")),
+        }
+        match table.get("color") {
+            Some(color) => self.check_value(keys, &format!("{name}.color"), color, &Kind::Rgb),
+            None => self.report(keys, format!("missing key `{name}.color`")),
+        }
+    }
+}
+
+// the theme file as a yaml value, or the syntax error with its location
+fn parse_value(
+    path: &Path,
+    contents: &str,
+    format: ConfigFormat,
+) -> Result<serde_yaml::Value, Diagnostic> {
+    let (message, location) = match format {
+        ConfigFormat::Yaml => match serde_yaml::from_str(contents) {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                // the location is reported separately
+                let message = e.to_string();
+                let message = match message.rfind(" at line ") {
+                    Some(i) => message[..i].to_string(),
+                    None => message,
+                };
+
+                (message, e.location().map(|l| (l.line(), l.column())))
+            }
+        },
+        ConfigFormat::Toml => match toml::from_str::<toml::Value>(contents) {
+            Ok(value) => match serde_yaml::to_value(value) {
+                Ok(value) => return Ok(value),
+                Err(e) => (e.to_string(), None),
+            },
+            Err(e) => (
+                e.message().trim_end().to_string(),
+                e.span().map(|span| line_column(contents, span.start)),
+            ),
+        },
+    };
+
+    Err(Diagnostic {
+        path: path.to_path_buf(),
+        line: location.map(|(line, _)| line),
+        column: location.map(|(_, column)| column),
+        message,
+    })
+}
+
+// the parsed file, unless it has a syntax error, and everything that is wrong with it
+fn lint_theme(path: &Path, contents: &str) -> (Option<serde_yaml::Value>, Vec<Diagnostic>) {
+    let format = ConfigFormat::from_path(path);
+
+    let value = match parse_value(path, contents, format) {
+        Ok(value) => value,
+        Err(diagnostic) => return (None, vec![diagnostic]),
+    };
+
+    let mut checker = Checker {
+        path,
+        contents,
+        format,
+        diagnostics: Vec::new(),
+    };
+    match &value {
+        // an empty file is `null` in yaml
+        serde_yaml::Value::Null => checker.report(&[], "missing key `colors`".to_string()),
+        serde_yaml::Value::Mapping(_) => checker.check_table(&[], "", &value, THEME),
+        _ => checker.report(&[], "the theme should be a table".to_string()),
+    }
+
+    (Some(value), checker.diagnostics)
+}
+
+/// Parse a theme file and collect everything that is wrong with it: syntax errors, malformed
+/// colors, missing required keys and unknown keys. Every key is checked, so all of the problems
+/// are reported at once. A syntax error is the only diagnostic, the rest of the file can't be
+/// read. The theme is returned when there are no diagnostics.
+pub fn validate_theme(path: &Path, contents: &str) -> (Option<YmlColor>, Vec<Diagnostic>) {
+    let (value, mut diagnostics) = lint_theme(path, contents);
+
+    let theme = match value {
+        Some(value) if diagnostics.is_empty() => match serde_yaml::from_value(value) {
+            Ok(theme) => Some(theme),
+            // the checks above missed something
+            Err(e) => {
+                diagnostics.push(Diagnostic {
+                    path: path.to_path_buf(),
+                    line: None,
+                    column: None,
+                    message: e.to_string(),
+                });
+                None
+            }
+        },
+        _ => None,
+    };
+
+    (theme, diagnostics)
+}
+
+/// The result of [`validate_themes`].
+#[derive(Debug)]
+pub struct ValidationReport {
+    /// Number of theme files that were checked.
+    pub themes: usize,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Validate every theme file in `store` and check that no two themes share a name or a file
+/// name. Themes with a syntax error have no name to compare.
+pub fn validate_themes(store: &ThemeStore) -> Result<ValidationReport, Error> {
+    let themes = store.theme_files()?;
+
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    // theme name -> first file that uses it
+    let mut names: Vec<(String, PathBuf)> = Vec::new();
+    let mut stems: Vec<(String, PathBuf)> = Vec::new();
+
+    for path in &themes {
+        let contents = match read_file(path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                diagnostics.push(Diagnostic {
+                    path: path.clone(),
+                    line: None,
+                    column: None,
+                    message: e.to_string(),
+                });
+                continue;
+            }
+        };
+
+        let (value, mut theme_diagnostics) = lint_theme(path, &contents);
+        diagnostics.append(&mut theme_diagnostics);
+
+        // `name` and `name.toml` would both match the same theme
+        let stem = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        match stems.iter().find(|(other, _)| *other == stem) {
+            Some((_, other)) => diagnostics.push(Diagnostic {
+                path: path.clone(),
+                line: None,
+                column: None,
+                message: format!(
+                    "duplicate theme file name `{stem}`, also used by {}",
+                    other.display()
+                ),
+            }),
+            None => stems.push((stem, path.clone())),
+        }
+
+        // also for themes with other errors, as long as the name can be read
+        let name = value
+            .as_ref()
+            .and_then(|value| value.get("colors"))
+            .and_then(|colors| colors.get("name"))
+            .and_then(|name| name.as_str())
+            .map(str::to_string);
+
+        if let Some(name) = name {
+            match names.iter().find(|(other, _)| *other == name) {
+                Some((_, other)) => diagnostics.push(Diagnostic {
+                    path: path.clone(),
+                    line: find_key_line(
+                        &contents,
+                        ConfigFormat::from_path(path),
+                        &["colors", "name"],
+                    ),
+                    column: None,
+                    message: format!(
+                        "duplicate theme name `{name}`, also used by {}",
+                        other.display()
+                    ),
+                }),
+                None => names.push((name, path.clone())),
+            }
+        }
+    }
+
+    Ok(ValidationReport {
+        themes: themes.len(),
+        diagnostics,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    const VALID: &str = r##"[colors]
+name = "Test"
+
+[colors.primary]
+background = "#000000"
+foreground = "#ffffff"
+
+[colors.normal]
+black = "#000000"
+red = "#ff0000"
+green = "#00ff00"
+yellow = "#ffff00"
+blue = "#0000ff"
+magenta = "#ff00ff"
+cyan = "#00ffff"
+white = "#ffffff"
+
+[colors.bright]
+black = "#000000"
+red = "#ff0000"
+green = "#00ff00"
+yellow = "#ffff00"
+blue = "#0000ff"
+magenta = "#ff00ff"
+cyan = "#00ffff"
+white = "#ffffff"
+"##;
+
+    // line, column and message of every diagnostic
+    fn found(diagnostics: &[Diagnostic]) -> Vec<(Option<usize>, Option<usize>, &str)> {
+        diagnostics
+            .iter()
+            .map(|d| (d.line, d.column, d.message.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn line_column_of_offset() {
+        assert_eq!(line_column("ab\ncd\n", 0), (1, 1));
+        assert_eq!(line_column("ab\ncd\n", 4), (2, 2));
+        assert_eq!(line_column("ab", 10), (1, 3));
+    }
+
+    #[test]
+    fn find_yaml_keys() {
+        let contents = "# comment\ncolors:\n  primary:\n    background: '#000000'\n  normal: {black: '#000000', red: '#ff0000'}\n";
+
+        assert_eq!(
+            find_key_line(contents, ConfigFormat::Yaml, &["colors"]),
+            Some(2)
+        );
+        assert_eq!(
+            find_key_line(
+                contents,
+                ConfigFormat::Yaml,
+                &["colors", "primary", "background"]
+            ),
+            Some(4)
+        );
+        assert_eq!(
+            find_key_line(contents, ConfigFormat::Yaml, &["colors", "normal", "red"]),
+            Some(5)
+        );
+        assert_eq!(
+            find_key_line(contents, ConfigFormat::Yaml, &["colors", "bright"]),
+            None
+        );
+    }
+
+    #[test]
+    fn find_toml_keys() {
+        let contents = "colors.name = \"x\"\n\n[colors.primary]\nbackground = \"#000000\"\n\n[colors.\"normal\"]\nred = \"#ff0000\"\n";
+
+        assert_eq!(
+            find_key_line(contents, ConfigFormat::Toml, &["colors", "name"]),
+            Some(1)
+        );
+        assert_eq!(
+            find_key_line(contents, ConfigFormat::Toml, &["colors", "primary"]),
+            Some(3)
+        );
+        assert_eq!(
+            find_key_line(
+                contents,
+                ConfigFormat::Toml,
+                &["colors", "primary", "background"]
+            ),
+            Some(4)
+        );
+        assert_eq!(
+            find_key_line(contents, ConfigFormat::Toml, &["colors", "normal", "red"]),
+            Some(7)
+        );
+        assert_eq!(
+            find_key_line(contents, ConfigFormat::Toml, &["colors", "bright"]),
+            None
+        );
+    }
+
+    #[test]
+    fn valid_theme() {
+        let (theme, diagnostics) = validate_theme(Path::new("test.toml"), VALID);
+
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        assert_eq!(theme.unwrap().colors.name.as_deref(), Some("Test"));
+    }
+
+    #[test]
+    fn every_problem_is_reported() {
+        let contents =
+            "colors:\n  bogus: 1\n  primary:\n    background: '#12'\n    foreground: nope\n";
+        let (theme, diagnostics) = validate_theme(Path::new("test.yml"), contents);
+
+        assert!(theme.is_none());
+        assert_eq!(
+            found(&diagnostics),
+            [
+                (Some(2), None, "unknown key `colors.bogus`"),
+                (
+                    Some(4),
+                    None,
+                    "`colors.primary.background`: invalid color '#12': expected 3 or 6 hex digits after '#' or 6 after '0x'"
+                ),
+                (
+                    Some(5),
+                    None,
+                    "`colors.primary.foreground`: invalid color 'nope': expected it to start with '#' or '0x'"
+                ),
+                (Some(1), None, "missing key `colors.normal`"),
+                (Some(1), None, "missing key `colors.bright`"),
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_problems_are_reported() {
+        let contents = VALID.replace("red = \"#ff0000\"\n", "")
+            + "\n[colors.cursor]\ncursor = 0x123456\n\n[[colors.indexed_colors]]\nindex = 3\ncolor = \"#ffffff\"\n";
+        let (_, diagnostics) = validate_theme(Path::new("test.toml"), &contents);
+
+        assert_eq!(
+            found(&diagnostics),
+            [
+                (
+                    Some(27),
+                    None,
+                    "`colors.cursor.cursor` should be a color in quotes, like \"#rrggbb\""
+                ),
+                (Some(8), None, "missing key `colors.normal.red`"),
+                (Some(17), None, "missing key `colors.bright.red`"),
+                (
+                    Some(29),
+                    None,
+                    "`colors.indexed_colors[0].index` should be a number from 16 to 255"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn syntax_errors_have_a_location() {
+        let (theme, diagnostics) = validate_theme(
+            Path::new("test.toml"),
+            "[colors]\nname = \"x\"\nname = \"y\"\n",
+        );
+
+        assert!(theme.is_none());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            (diagnostics[0].line, diagnostics[0].column),
+            (Some(3), Some(1))
+        );
+    }
+
+    // a themes dir with `files` in it, tests run in parallel
+    fn themes_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ats-validate-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        for (file, contents) in files {
+            let path = dir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+
+        dir
+    }
+
+    #[test]
+    fn duplicate_names_and_stems() {
+        let other = VALID.replace("name = \"Test\"", "name = \"Other\"");
+        let dir = themes_dir(
+            "duplicates",
+            &[
+                ("a.toml", VALID),
+                ("b.toml", VALID),
+                ("c.toml", &other),
+                ("sub/c.toml", &other),
+            ],
+        );
+
+        let report = validate_themes(&ThemeStore::new(&dir)).unwrap();
+        let messages: Vec<String> = report
+            .diagnostics
+            .iter()
+            .map(|d| d.to_string().replace(&*dir.to_string_lossy(), ""))
+            .collect();
+
+        assert_eq!(report.themes, 4);
+        assert_eq!(
+            messages,
+            [
+                "/b.toml:2: duplicate theme name `Test`, also used by /a.toml",
+                "/sub/c.toml: duplicate theme file name `c`, also used by /c.toml",
+                "/sub/c.toml:2: duplicate theme name `Other`, also used by /c.toml",
+            ]
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn duplicate_names_of_broken_themes() {
+        let broken = VALID.replace("#ff0000", "nope");
+        let dir = themes_dir("broken", &[("a.toml", VALID), ("b.toml", &broken)]);
+
+        let report = validate_themes(&ThemeStore::new(&dir)).unwrap();
+
+        assert!(report.diagnostics.iter().any(|d| d.message
+            == format!(
+                "duplicate theme name `Test`, also used by {}",
+                dir.join("a.toml").display()
+            )));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}